@@ -1,20 +1,13 @@
-use minimp3_fixed::{Decoder, Error, Frame};
+use minimp3_fixed::{Decoder, Frame};
 
 use std::fs::File;
 
 fn main() {
-    let mut decoder =
+    let decoder =
         Decoder::new(File::open("minimp3-sys/minimp3/vectors/M2L3_bitrate_24_all.bit").unwrap());
 
-    loop {
-        match decoder.next_frame() {
-            Ok(Frame {
-                data,
-                channels,
-                ..
-            }) => println!("Decoded {} samples", data.len() / channels),
-            Err(Error::Eof) => break,
-            Err(e) => panic!("{:?}", e),
-        }
+    for frame in decoder {
+        let Frame { data, channels, .. } = frame.unwrap();
+        println!("Decoded {} samples", data.len() / channels)
     }
 }
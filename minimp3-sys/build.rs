@@ -7,6 +7,7 @@ fn main() {
     cc::Build::new()
         .include("minimp3/")
         .file("minimp3.c")
+        .file("minimp3_f32.c")
         .define("MINIMP3_IMPLEMENTATION", None)
         .compile("minimp3");
 
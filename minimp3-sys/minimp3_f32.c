@@ -0,0 +1,28 @@
+/* Second build of minimp3 which outputs f32 samples. Every exported symbol is
+ * renamed with a `_f32` suffix so it can be linked next to the i16 build. */
+#define MINIMP3_FLOAT_OUTPUT
+
+#define mp3dec_init mp3dec_init_f32
+#define mp3dec_decode_frame mp3dec_decode_frame_f32
+#define mp3dec_detect_buf mp3dec_detect_buf_f32
+#define mp3dec_detect_cb mp3dec_detect_cb_f32
+#define mp3dec_load_buf mp3dec_load_buf_f32
+#define mp3dec_load_cb mp3dec_load_cb_f32
+#define mp3dec_iterate_buf mp3dec_iterate_buf_f32
+#define mp3dec_iterate_cb mp3dec_iterate_cb_f32
+#define mp3dec_ex_open_buf mp3dec_ex_open_buf_f32
+#define mp3dec_ex_open_cb mp3dec_ex_open_cb_f32
+#define mp3dec_ex_close mp3dec_ex_close_f32
+#define mp3dec_ex_seek mp3dec_ex_seek_f32
+#define mp3dec_ex_read_frame mp3dec_ex_read_frame_f32
+#define mp3dec_ex_read mp3dec_ex_read_f32
+#define mp3dec_detect mp3dec_detect_f32
+#define mp3dec_load mp3dec_load_f32
+#define mp3dec_iterate mp3dec_iterate_f32
+#define mp3dec_ex_open mp3dec_ex_open_f32
+#define mp3dec_detect_w mp3dec_detect_w_f32
+#define mp3dec_load_w mp3dec_load_w_f32
+#define mp3dec_iterate_w mp3dec_iterate_w_f32
+#define mp3dec_ex_open_w mp3dec_ex_open_w_f32
+
+#include "wrapper.h"
@@ -1,3 +1,162 @@
 #![allow(bad_style)]
 
 include!(concat!(env!("CARGO_MANIFEST_DIR"), "/bindings.rs"));
+
+/// Bindings to the second build of minimp3, compiled with
+/// `MINIMP3_FLOAT_OUTPUT`, which decodes to `f32` samples.
+///
+/// Only the types holding samples differ from the `i16` build, every other
+/// type is shared with the crate root.
+pub mod f32 {
+    use super::{
+        mp3dec_frame_info_t, mp3dec_index_t, mp3dec_io_t, mp3dec_map_info_t, mp3dec_t, size_t,
+        MP3D_ITERATE_CB, MP3D_PROGRESS_CB,
+    };
+    use std::os::raw::{c_char, c_int};
+
+    pub type mp3d_sample_t = f32;
+
+    #[repr(C)]
+    #[derive(Debug, Copy, Clone)]
+    pub struct mp3dec_file_info_t {
+        pub buffer: *mut mp3d_sample_t,
+        pub samples: size_t,
+        pub channels: c_int,
+        pub hz: c_int,
+        pub layer: c_int,
+        pub avg_bitrate_kbps: c_int,
+    }
+
+    #[repr(C)]
+    #[derive(Copy, Clone)]
+    pub struct mp3dec_ex_t {
+        pub mp3d: mp3dec_t,
+        pub file: mp3dec_map_info_t,
+        pub io: *mut mp3dec_io_t,
+        pub index: mp3dec_index_t,
+        pub offset: u64,
+        pub samples: u64,
+        pub detected_samples: u64,
+        pub cur_sample: u64,
+        pub start_offset: u64,
+        pub end_offset: u64,
+        pub info: mp3dec_frame_info_t,
+        pub buffer: [mp3d_sample_t; 2304usize],
+        pub input_consumed: size_t,
+        pub input_filled: size_t,
+        pub is_file: c_int,
+        pub flags: c_int,
+        pub vbr_tag_found: c_int,
+        pub indexes_built: c_int,
+        pub free_format_bytes: c_int,
+        pub buffer_samples: c_int,
+        pub buffer_consumed: c_int,
+        pub to_skip: c_int,
+        pub start_delay: c_int,
+        pub last_error: c_int,
+    }
+
+    extern "C" {
+        #[link_name = "mp3dec_init_f32"]
+        pub fn mp3dec_init(dec: *mut mp3dec_t);
+        #[link_name = "mp3dec_decode_frame_f32"]
+        pub fn mp3dec_decode_frame(
+            dec: *mut mp3dec_t,
+            mp3: *const u8,
+            mp3_bytes: c_int,
+            pcm: *mut mp3d_sample_t,
+            info: *mut mp3dec_frame_info_t,
+        ) -> c_int;
+        pub fn mp3dec_f32_to_s16(input: *const f32, output: *mut i16, num_samples: c_int);
+        #[link_name = "mp3dec_detect_buf_f32"]
+        pub fn mp3dec_detect_buf(buf: *const u8, buf_size: size_t) -> c_int;
+        #[link_name = "mp3dec_detect_cb_f32"]
+        pub fn mp3dec_detect_cb(io: *mut mp3dec_io_t, buf: *mut u8, buf_size: size_t) -> c_int;
+        #[link_name = "mp3dec_load_buf_f32"]
+        pub fn mp3dec_load_buf(
+            dec: *mut mp3dec_t,
+            buf: *const u8,
+            buf_size: size_t,
+            info: *mut mp3dec_file_info_t,
+            progress_cb: MP3D_PROGRESS_CB,
+            user_data: *mut std::os::raw::c_void,
+        ) -> c_int;
+        #[link_name = "mp3dec_load_cb_f32"]
+        pub fn mp3dec_load_cb(
+            dec: *mut mp3dec_t,
+            io: *mut mp3dec_io_t,
+            buf: *mut u8,
+            buf_size: size_t,
+            info: *mut mp3dec_file_info_t,
+            progress_cb: MP3D_PROGRESS_CB,
+            user_data: *mut std::os::raw::c_void,
+        ) -> c_int;
+        #[link_name = "mp3dec_iterate_buf_f32"]
+        pub fn mp3dec_iterate_buf(
+            buf: *const u8,
+            buf_size: size_t,
+            callback: MP3D_ITERATE_CB,
+            user_data: *mut std::os::raw::c_void,
+        ) -> c_int;
+        #[link_name = "mp3dec_iterate_cb_f32"]
+        pub fn mp3dec_iterate_cb(
+            io: *mut mp3dec_io_t,
+            buf: *mut u8,
+            buf_size: size_t,
+            callback: MP3D_ITERATE_CB,
+            user_data: *mut std::os::raw::c_void,
+        ) -> c_int;
+        #[link_name = "mp3dec_ex_open_buf_f32"]
+        pub fn mp3dec_ex_open_buf(
+            dec: *mut mp3dec_ex_t,
+            buf: *const u8,
+            buf_size: size_t,
+            flags: c_int,
+        ) -> c_int;
+        #[link_name = "mp3dec_ex_open_cb_f32"]
+        pub fn mp3dec_ex_open_cb(
+            dec: *mut mp3dec_ex_t,
+            io: *mut mp3dec_io_t,
+            flags: c_int,
+        ) -> c_int;
+        #[link_name = "mp3dec_ex_close_f32"]
+        pub fn mp3dec_ex_close(dec: *mut mp3dec_ex_t);
+        #[link_name = "mp3dec_ex_seek_f32"]
+        pub fn mp3dec_ex_seek(dec: *mut mp3dec_ex_t, position: u64) -> c_int;
+        #[link_name = "mp3dec_ex_read_frame_f32"]
+        pub fn mp3dec_ex_read_frame(
+            dec: *mut mp3dec_ex_t,
+            buf: *mut *mut mp3d_sample_t,
+            frame_info: *mut mp3dec_frame_info_t,
+            max_samples: size_t,
+        ) -> size_t;
+        #[link_name = "mp3dec_ex_read_f32"]
+        pub fn mp3dec_ex_read(
+            dec: *mut mp3dec_ex_t,
+            buf: *mut mp3d_sample_t,
+            samples: size_t,
+        ) -> size_t;
+        #[link_name = "mp3dec_detect_f32"]
+        pub fn mp3dec_detect(file_name: *const c_char) -> c_int;
+        #[link_name = "mp3dec_load_f32"]
+        pub fn mp3dec_load(
+            dec: *mut mp3dec_t,
+            file_name: *const c_char,
+            info: *mut mp3dec_file_info_t,
+            progress_cb: MP3D_PROGRESS_CB,
+            user_data: *mut std::os::raw::c_void,
+        ) -> c_int;
+        #[link_name = "mp3dec_iterate_f32"]
+        pub fn mp3dec_iterate(
+            file_name: *const c_char,
+            callback: MP3D_ITERATE_CB,
+            user_data: *mut std::os::raw::c_void,
+        ) -> c_int;
+        #[link_name = "mp3dec_ex_open_f32"]
+        pub fn mp3dec_ex_open(
+            dec: *mut mp3dec_ex_t,
+            file_name: *const c_char,
+            flags: c_int,
+        ) -> c_int;
+    }
+}
@@ -0,0 +1,177 @@
+//! Parsing of APEv1 and APEv2 tags, which some encoders append to MP3
+//! streams. They are found through [`Trailer`](crate::trailer::Trailer).
+
+use crate::id3::{Picture, PICTURE_TYPES};
+
+/// The size of the footer of a tag, and of its optional header.
+pub const FOOTER_SIZE: usize = 32;
+
+const FLAG_HAS_HEADER: u32 = 1 << 31;
+const FLAG_IS_HEADER: u32 = 1 << 29;
+
+/// An APE tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApeTag {
+    /// 1000 for APEv1, 2000 for APEv2.
+    pub version: u32,
+    pub items: Vec<ApeItem>,
+}
+
+/// An item of an [`ApeTag`](ApeTag).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApeItem {
+    /// The key, like `Title`. Keys are compared ignoring case.
+    pub key: String,
+    pub value: ApeValue,
+}
+
+/// The value of an [`ApeItem`](ApeItem).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApeValue {
+    /// UTF-8 text. Lists of values are separated by null characters.
+    Text(String),
+    Binary(Vec<u8>),
+    /// A URL or file name pointing to the value.
+    Locator(String),
+}
+
+/// The size of the tag ending with `footer`, header included if it has one.
+/// Returns `None` if `footer` isn't an APE tag footer.
+pub fn tag_size(footer: &[u8]) -> Option<usize> {
+    let footer = footer.get(..FOOTER_SIZE)?;
+    if &footer[..8] != b"APETAGEX" {
+        return None;
+    }
+    let size = u32_le(&footer[12..16]) as usize;
+    let flags = u32_le(&footer[20..24]);
+    if flags & FLAG_IS_HEADER != 0 || size < FOOTER_SIZE {
+        return None;
+    }
+    // The header isn't counted in the size, and APEv1 tags never have one.
+    let header = flags & FLAG_HAS_HEADER != 0 && u32_le(&footer[8..12]) >= 2000;
+    Some(size + header as usize * FOOTER_SIZE)
+}
+
+impl ApeTag {
+    /// Parses the tag filling `data`, which ends with the footer. Items which
+    /// can't be parsed end the list of items.
+    pub fn parse(data: &[u8]) -> Option<ApeTag> {
+        let footer = &data[data.len().checked_sub(FOOTER_SIZE)?..];
+        if data.len() < tag_size(footer)? {
+            return None;
+        }
+        let version = u32_le(&footer[8..12]);
+        // Counts the items and the footer.
+        let size = u32_le(&footer[12..16]) as usize;
+        let count = u32_le(&footer[16..20]) as usize;
+        let mut items = &data[data.len() - size..data.len() - FOOTER_SIZE];
+
+        let mut tag = ApeTag {
+            version,
+            items: Vec::new(),
+        };
+        while tag.items.len() < count {
+            match parse_item(items) {
+                Some((item, rest)) => {
+                    tag.items.push(item);
+                    items = rest;
+                }
+                None => break,
+            }
+        }
+        Some(tag)
+    }
+
+    /// The first item with the given key, ignoring case.
+    pub fn get(&self, key: &str) -> Option<&ApeItem> {
+        self.items
+            .iter()
+            .find(|item| item.key.eq_ignore_ascii_case(key))
+    }
+
+    /// The text of the item with the given key.
+    pub fn text(&self, key: &str) -> Option<&str> {
+        match &self.get(key)?.value {
+            ApeValue::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// The title (`Title`).
+    pub fn title(&self) -> Option<&str> {
+        self.text("Title")
+    }
+
+    /// The artist (`Artist`).
+    pub fn artist(&self) -> Option<&str> {
+        self.text("Artist")
+    }
+
+    /// The album (`Album`).
+    pub fn album(&self) -> Option<&str> {
+        self.text("Album")
+    }
+
+    /// The binary `Cover Art (...)` items, as pictures. The MIME type is
+    /// guessed from the file name the item starts with, or the image data.
+    pub fn pictures(&self) -> impl Iterator<Item = Picture> + '_ {
+        self.items.iter().filter_map(|item| {
+            let prefix = item.key.get(..11)?;
+            if !prefix.eq_ignore_ascii_case("Cover Art (") {
+                return None;
+            }
+            let kind = item.key[11..].strip_suffix(')')?;
+            let data = match &item.value {
+                ApeValue::Binary(data) => data,
+                _ => return None,
+            };
+            let name_len = data.iter().position(|&b| b == 0)?;
+            let description = String::from_utf8_lossy(&data[..name_len]).into_owned();
+            let data = data[name_len + 1..].to_vec();
+            let picture_type = PICTURE_TYPES
+                .iter()
+                .position(|name| name.eq_ignore_ascii_case(kind))
+                .unwrap_or(0) as u8;
+            Some(Picture {
+                mime_type: mime_type(&description, &data).to_owned(),
+                picture_type,
+                description,
+                data,
+            })
+        })
+    }
+}
+
+fn mime_type(file_name: &str, data: &[u8]) -> &'static str {
+    let extension = file_name.rsplit('.').next().unwrap_or_default();
+    match &extension.to_ascii_lowercase()[..] {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "bmp" => "image/bmp",
+        _ if data.starts_with(&[0xFF, 0xD8]) => "image/jpeg",
+        _ if data.starts_with(b"\x89PNG") => "image/png",
+        _ if data.starts_with(b"GIF8") => "image/gif",
+        _ if data.starts_with(b"BM") => "image/bmp",
+        _ => "application/octet-stream",
+    }
+}
+
+fn parse_item(data: &[u8]) -> Option<(ApeItem, &[u8])> {
+    let size = u32_le(data.get(..4)?) as usize;
+    let flags = u32_le(data.get(4..8)?);
+    let key_len = data[8..].iter().position(|&b| b == 0)?;
+    let key = String::from_utf8_lossy(&data[8..8 + key_len]).into_owned();
+    let value_start = 8 + key_len + 1;
+    let value = data.get(value_start..value_start + size)?;
+    let value = match (flags >> 1) & 0b11 {
+        1 => ApeValue::Binary(value.to_vec()),
+        2 => ApeValue::Locator(String::from_utf8_lossy(value).into_owned()),
+        _ => ApeValue::Text(String::from_utf8_lossy(value).into_owned()),
+    };
+    Some((ApeItem { key, value }, &data[value_start + size..]))
+}
+
+fn u32_le(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
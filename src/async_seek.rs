@@ -0,0 +1,276 @@
+use crate::{
+    buffered::BufferedDecoder,
+    gain::{GainOptions, ReplayGain},
+    id3::Tag,
+    into_frame,
+    trailer::Trailer,
+    Error, Frame, FrameInfo, Sample, MAX_SAMPLES_PER_FRAME,
+};
+use std::io::SeekFrom;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+
+// Frames decoded and thrown away before the frame a seek lands in, so the
+// bit reservoir and the synthesis filters are filled. minimp3 does the same in
+// `mp3dec_ex_seek`.
+const PREDECODE_FRAMES: usize = crate::ffi::MINIMP3_PREDECODE_FRAMES as usize;
+// The bit reservoir can reach this far back.
+const MAX_RESERVOIR_BYTES: u64 = 511;
+
+#[derive(Debug, Clone, Copy)]
+struct IndexedFrame {
+    // Index of the first sample of the frame, counting every channel.
+    sample: u64,
+    offset: u64,
+}
+
+/// A sample level seekable MP3 decoder for async readers.
+///
+/// The async counterpart of [`SeekDecoder`](crate::SeekDecoder). minimp3 can
+/// only build its seek index through blocking callbacks, so this decoder
+/// builds its own: [`new`](AsyncSeekDecoder::new) reads through the whole
+/// stream once, parsing frame headers without decoding them. Seeking then
+/// jumps to the frame holding the sample and decodes from a few frames
+/// before it.
+///
+/// Unlike `SeekDecoder` this doesn't trim the samples added by the encoder,
+/// and the Xing/Info frame at the start of a VBR file decodes to silence.
+pub struct AsyncSeekDecoder<R, S = i16> {
+    reader: R,
+    decoder: BufferedDecoder<S>,
+    buffer_refill: Box<[u8; MAX_SAMPLES_PER_FRAME * 5]>,
+    index: Vec<IndexedFrame>,
+    samples: u64,
+    // Index of the next sample to return, counting every channel.
+    position: u64,
+    // Decoded samples of the current frame, `pcm[pcm_start..]` are left.
+    pcm: Vec<S>,
+    pcm_start: usize,
+    info: Option<FrameInfo>,
+}
+
+impl<R> AsyncSeekDecoder<R>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+{
+    /// Creates a new `AsyncSeekDecoder`, consuming the `reader`. This reads
+    /// the whole stream to build the seek index.
+    pub async fn new(reader: R) -> Result<AsyncSeekDecoder<R>, Error> {
+        Self::open(reader).await
+    }
+}
+
+impl<R> AsyncSeekDecoder<R, f32>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+{
+    /// Creates a new `AsyncSeekDecoder` producing `f32` samples, consuming
+    /// the `reader`. This reads the whole stream to build the seek index.
+    pub async fn new_float(reader: R) -> Result<AsyncSeekDecoder<R, f32>, Error> {
+        Self::open(reader).await
+    }
+}
+
+impl<R, S> AsyncSeekDecoder<R, S>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+    S: Sample,
+{
+    async fn open(reader: R) -> Result<AsyncSeekDecoder<R, S>, Error> {
+        let mut decoder = AsyncSeekDecoder {
+            reader,
+            decoder: BufferedDecoder::new(),
+            buffer_refill: Box::new([0; MAX_SAMPLES_PER_FRAME * 5]),
+            index: Vec::new(),
+            samples: 0,
+            position: 0,
+            pcm: Vec::new(),
+            pcm_start: 0,
+            info: None,
+        };
+        decoder.build_index().await?;
+        decoder.seek_samples(0).await?;
+        Ok(decoder)
+    }
+
+    async fn build_index(&mut self) -> Result<(), Error> {
+        let start = self.reader.stream_position().await?;
+        let trailer = read_trailer(&mut self.reader).await?;
+        self.reader.seek(SeekFrom::Start(start)).await?;
+        self.decoder.reset(start, 0);
+        self.decoder.set_trailer(trailer, start);
+        let mut input = None;
+        loop {
+            input = match self.decoder.read_header(input) {
+                Ok(Some(info)) => {
+                    self.index.push(IndexedFrame {
+                        sample: self.samples,
+                        offset: info.offset.unwrap_or_default(),
+                    });
+                    self.samples += info.samples as u64;
+                    None
+                }
+                Ok(None) => {
+                    let read_bytes = self.reader.read(&mut self.buffer_refill[..]).await?;
+                    Some(&self.buffer_refill[..read_bytes])
+                }
+                Err(Error::Eof) => return Ok(()),
+                Err(e) => return Err(e),
+            };
+        }
+    }
+
+    /// The total number of samples in the stream, counting every channel.
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// The ID3v2 tag at the start of the stream, if it has one.
+    pub fn tag(&self) -> Option<&Tag> {
+        self.decoder.tag()
+    }
+
+    /// The tags at the end of the stream, where decoding stops.
+    pub fn trailer(&self) -> &Trailer {
+        self.decoder
+            .trailer()
+            .expect("the trailer is read when opening")
+    }
+
+    /// Applies ReplayGain to the decoded audio, or stops applying it with
+    /// `None`. Off by default.
+    pub fn set_replay_gain(&mut self, options: Option<GainOptions>) {
+        self.decoder.set_replay_gain(options);
+    }
+
+    /// The ReplayGain values of the stream, from the ID3v2 tag at its start,
+    /// the LAME tag, and the tags at its end.
+    pub fn replay_gain(&self) -> ReplayGain {
+        self.decoder.replay_gain()
+    }
+
+    /// Decodes the rest of the current frame, or the next frame. Returns
+    /// [`Error::Eof`](Error::Eof) once the end of the stream is reached.
+    pub async fn decode_frame(&mut self) -> Result<Frame<S>, Error> {
+        let mut info = self.fill_pcm().await?;
+        let data = self.pcm[self.pcm_start..].to_vec();
+        info.samples = data.len();
+        info.sample_position = self.position;
+        self.position += data.len() as u64;
+        self.pcm_start = self.pcm.len();
+        Ok(into_frame(data, info))
+    }
+
+    /// Returns the number of samples that were set
+    /// Will be zero at end of stream
+    pub async fn read_samples(&mut self, buf: &mut [S]) -> Result<usize, Error> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            match self.fill_pcm().await {
+                Ok(_) => {}
+                Err(Error::Eof) => break,
+                Err(e) => return Err(e),
+            }
+            let left = &self.pcm[self.pcm_start..];
+            let len = left.len().min(buf.len() - filled);
+            buf[filled..filled + len].copy_from_slice(&left[..len]);
+            filled += len;
+            self.pcm_start += len;
+            self.position += len as u64;
+        }
+        Ok(filled)
+    }
+
+    /// Seek to the given sample index
+    pub async fn seek_samples(&mut self, sample: u64) -> Result<(), Error> {
+        let sample = sample.min(self.samples);
+        let target = self
+            .index
+            .partition_point(|frame| frame.sample <= sample)
+            .saturating_sub(1);
+
+        let mut start = target;
+        while start > 0
+            && (target - start < PREDECODE_FRAMES
+                || self.index[target].offset - self.index[start].offset < MAX_RESERVOIR_BYTES)
+        {
+            start -= 1;
+        }
+
+        let (offset, start_sample) = match self.index.get(start) {
+            Some(frame) => (frame.offset, frame.sample),
+            None => (self.reader.stream_position().await?, self.samples),
+        };
+        self.reader.seek(SeekFrom::Start(offset)).await?;
+        self.decoder.reset(offset, start_sample);
+        self.position = sample;
+        self.pcm_start = self.pcm.len();
+        Ok(())
+    }
+
+    /// This mp3s sample rate in hertz, as of the last decoded frame.
+    pub fn current_sample_rate(&self) -> i32 {
+        self.info.map_or(0, |info| info.sample_rate)
+    }
+
+    /// The number of channels in this mp3, as of the last decoded frame.
+    pub fn current_channels(&self) -> usize {
+        self.info.map_or(0, |info| info.channels)
+    }
+
+    /// Destroy the decoder and return the inner reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    // Makes sure samples of the current frame are left, decoding frames and
+    // dropping the samples before `position` as needed.
+    async fn fill_pcm(&mut self) -> Result<FrameInfo, Error> {
+        while self.pcm_start == self.pcm.len() {
+            let info = self.next_frame().await?;
+            self.pcm.truncate(info.samples);
+
+            let offset = info.offset.unwrap_or_default();
+            let frame_sample = match self.index.binary_search_by_key(&offset, |f| f.offset) {
+                Ok(i) => self.index[i].sample,
+                Err(_) => self.position,
+            };
+            // A frame lost to a broken bit reservoir leaves a gap.
+            self.position = self.position.max(frame_sample);
+            let skip = self.position - frame_sample;
+            self.pcm_start = (skip as usize).min(self.pcm.len());
+            self.info = Some(info);
+        }
+        Ok(self.info.unwrap())
+    }
+
+    async fn next_frame(&mut self) -> Result<FrameInfo, Error> {
+        self.pcm.resize(MAX_SAMPLES_PER_FRAME, S::default());
+        let mut input = None;
+        loop {
+            if let Some(info) = self.decoder.read_frame(input, &mut self.pcm)? {
+                return Ok(info);
+            }
+            let read_bytes = self.reader.read(&mut self.buffer_refill[..]).await?;
+            input = Some(&self.buffer_refill[..read_bytes]);
+        }
+    }
+}
+
+/// The async counterpart of [`Trailer::read`](Trailer::read).
+pub(crate) async fn read_trailer<R>(reader: &mut R) -> std::io::Result<Trailer>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+{
+    let len = reader.seek(SeekFrom::End(0)).await?;
+    let mut tail = Vec::new();
+    loop {
+        match Trailer::step(&tail, len) {
+            Ok(trailer) => return Ok(trailer),
+            Err(start) => {
+                tail = vec![0; (len - start) as usize];
+                reader.seek(SeekFrom::Start(start)).await?;
+                reader.read_exact(&mut tail).await?;
+            }
+        }
+    }
+}
@@ -0,0 +1,484 @@
+use crate::{
+    ffi,
+    gain::{self, GainOptions, ReplayGain},
+    header::FrameHeader,
+    id3::{self, Tag},
+    trailer::Trailer,
+    vbr::{LameTag, VbrHeader},
+    Error, FrameInfo, FrameRef, Sample, MAX_SAMPLES_PER_FRAME,
+};
+use slice_ring_buffer::SliceRingBuffer;
+use std::mem;
+
+const BUFFER_SIZE: usize = MAX_SAMPLES_PER_FRAME * 15;
+const REFILL_TRIGGER: usize = MAX_SAMPLES_PER_FRAME * 8;
+// Enough bytes to hold a free format frame and the frame after it, which
+// minimp3 needs to find the frame.
+const MAX_PARTIAL_FRAME: usize = 3 * 2304;
+// The largest ID3v2 tag buffered to be parsed. The size comes from the
+// stream, so larger tags are skipped as they arrive instead.
+const MAX_TAG_SIZE: usize = 8 * 1024 * 1024;
+
+/// The IO-agnostic part of [`Decoder`](crate::Decoder) and
+/// [`PushDecoder`](crate::PushDecoder): owns the minimp3 decoder and the
+/// input buffer, and turns pushed bytes into frames.
+///
+/// Readers drive it through [`read_frame`](BufferedDecoder::read_frame),
+/// handing it whatever they read until it returns a frame, and only do the
+/// reading themselves. An empty read means the reader is at its end.
+pub(crate) struct BufferedDecoder<S> {
+    buffer: SliceRingBuffer<u8>,
+    decoder: Box<ffi::mp3dec_t>,
+    pcm: Vec<S>,
+    eof: bool,
+    min_buffered: usize,
+    // Stream offset of the first buffered byte.
+    offset: u64,
+    // Stream offset right after the last frame returned.
+    frame_end: u64,
+    // Samples returned so far, counting every channel.
+    sample_position: u64,
+    // The header of the first frame found, and the VBR header it held.
+    first_frame: Option<FrameHeader>,
+    vbr: Option<VbrHeader>,
+    gapless: bool,
+    trim: Option<Trim>,
+    // The ID3v2 tag at the start of the stream, once `tag_done`. While
+    // waiting for the rest of the tag, `tag_wanted` is its size, and while
+    // skipping a tag too large to parse, `tag_skipped` the bytes left of it.
+    tag: Option<Tag>,
+    tag_done: bool,
+    tag_wanted: usize,
+    tag_skipped: usize,
+    // The tags at the end of the stream, and the offset they start at, where
+    // the input is cut off.
+    trailer: Option<Trailer>,
+    end: Option<u64>,
+    // The gains found in the tags so far, and how to apply them.
+    replay_gain: ReplayGain,
+    gain: Option<GainOptions>,
+}
+
+// The range of decoded samples which is audio when trimming for gapless
+// playback, counting every channel.
+#[derive(Debug, Clone, Copy)]
+struct Trim {
+    start: u64,
+    end: Option<u64>,
+}
+
+impl Trim {
+    fn new(header: &FrameHeader, vbr: &VbrHeader) -> Self {
+        let channels = header.channels() as u64;
+        let frame_samples = header.samples_per_frame() as u64;
+        // Without a LAME tag only the VBR header frame is known to be
+        // added, like in minimp3.
+        let (start_trim, end_trim) = vbr.lame.as_ref().map_or((0, 0), LameTag::trimmed_samples);
+        // The frame holding the VBR header decodes to silence.
+        let start = frame_samples + start_trim;
+        let end = vbr.frames.map(|frames| {
+            let decoded = frames as u64 * frame_samples;
+            (frame_samples + decoded.saturating_sub(end_trim)) * channels
+        });
+        Trim {
+            start: start * channels,
+            end,
+        }
+    }
+
+    // Moves the audio part of the frame in `pcm` to its start.
+    fn apply<S: Copy>(&self, pcm: &mut [S], mut info: FrameInfo) -> Option<FrameInfo> {
+        let frame_start = info.sample_position;
+        let frame_end = frame_start + info.samples as u64;
+        let start = self.start.max(frame_start);
+        let end = self.end.map_or(frame_end, |end| end.min(frame_end));
+        if start >= end {
+            return None;
+        }
+
+        let from = (start - frame_start) as usize;
+        let len = (end - start) as usize;
+        pcm.copy_within(from..from + len, 0);
+        info.samples = len;
+        info.sample_position = start - self.start;
+        Some(info)
+    }
+}
+
+// Explicitly impl [Send] for [BufferedDecoder]s. This isn't a great idea and
+// should probably be removed in the future. The only reason it's here is that
+// [SliceRingBuffer] doesn't implement [Send] (since it uses raw pointers
+// internally), even though it's safe to send it across thread boundaries.
+unsafe impl<S: Send> Send for BufferedDecoder<S> {}
+
+impl<S: Sample> BufferedDecoder<S> {
+    /// A decoder which keeps a few frames buffered before decoding, to make
+    /// sure a frame sync is followed by more frames.
+    pub(crate) fn new() -> Self {
+        Self::with_min_buffered(REFILL_TRIGGER)
+    }
+
+    /// A decoder which decodes frames as soon as they are complete.
+    pub(crate) fn eager() -> Self {
+        Self::with_min_buffered(0)
+    }
+
+    fn with_min_buffered(min_buffered: usize) -> Self {
+        let mut minidec = unsafe { Box::new(mem::zeroed()) };
+        unsafe { ffi::mp3dec_init(&mut *minidec) }
+
+        Self {
+            buffer: SliceRingBuffer::with_capacity(BUFFER_SIZE),
+            decoder: minidec,
+            pcm: Vec::new(),
+            eof: false,
+            min_buffered,
+            offset: 0,
+            frame_end: 0,
+            sample_position: 0,
+            first_frame: None,
+            vbr: None,
+            gapless: false,
+            trim: None,
+            tag: None,
+            tag_done: false,
+            tag_wanted: 0,
+            tag_skipped: 0,
+            trailer: None,
+            end: None,
+            replay_gain: ReplayGain::default(),
+            gain: None,
+        }
+    }
+
+    /// Drops the buffered input and the decoder state, to continue decoding
+    /// at stream `offset`, where the sample at `sample_position` starts.
+    #[cfg_attr(not(feature = "async_tokio"), allow(dead_code))]
+    pub(crate) fn reset(&mut self, offset: u64, sample_position: u64) {
+        self.buffer.clear();
+        unsafe { ffi::mp3dec_init(&mut *self.decoder) }
+        self.eof = false;
+        self.tag_skipped = 0;
+        self.offset = offset;
+        self.frame_end = offset;
+        self.sample_position = sample_position;
+    }
+
+    /// The Xing/Info or VBRI header of the first frame.
+    pub(crate) fn vbr_header(&self) -> Option<&VbrHeader> {
+        self.vbr.as_ref()
+    }
+
+    /// The header of the first frame.
+    pub(crate) fn first_frame(&self) -> Option<&FrameHeader> {
+        self.first_frame.as_ref()
+    }
+
+    /// The number of samples of the stream according to its VBR header,
+    /// counting every channel. Doesn't count the trimmed samples in gapless
+    /// mode.
+    pub(crate) fn total_samples(&self) -> Option<u64> {
+        match self.trim {
+            Some(Trim {
+                start,
+                end: Some(end),
+            }) => Some(end.saturating_sub(start)),
+            _ => self.vbr.as_ref()?.total_samples(self.first_frame.as_ref()?),
+        }
+    }
+
+    /// Whether to trim the VBR header frame and the samples the encoder
+    /// added, as given by the LAME tag.
+    pub(crate) fn set_gapless(&mut self, gapless: bool) {
+        self.gapless = gapless;
+        self.update_trim();
+    }
+
+    fn update_trim(&mut self) {
+        self.trim = match (&self.first_frame, &self.vbr) {
+            (Some(header), Some(vbr)) if self.gapless => Some(Trim::new(header, vbr)),
+            _ => None,
+        };
+    }
+
+    /// The ReplayGain values found in the tags read so far.
+    pub(crate) fn replay_gain(&self) -> ReplayGain {
+        self.replay_gain
+    }
+
+    /// Applies ReplayGain to the decoded frames.
+    pub(crate) fn set_replay_gain(&mut self, options: Option<GainOptions>) {
+        self.gain = options;
+    }
+
+    fn update_replay_gain(&mut self) {
+        self.replay_gain = self.find_replay_gain(self.trailer.as_ref());
+    }
+
+    fn find_replay_gain(&self, trailer: Option<&Trailer>) -> ReplayGain {
+        let lame = self.vbr.as_ref().and_then(|vbr| vbr.lame.as_ref());
+        ReplayGain::find(self.tag.as_ref(), trailer, lame)
+    }
+
+    /// The ID3v2 tag at the start of the stream.
+    pub(crate) fn tag(&self) -> Option<&Tag> {
+        self.tag.as_ref()
+    }
+
+    /// The tags at the end of the stream.
+    pub(crate) fn trailer(&self) -> Option<&Trailer> {
+        self.trailer.as_ref()
+    }
+
+    /// Drops the input from the start of `trailer` on, so its tags aren't
+    /// decoded. `position` is the stream offset right after the pushed input.
+    pub(crate) fn set_trailer(&mut self, trailer: Trailer, position: u64) -> &Trailer {
+        let pushed = self.offset + self.buffer.len() as u64;
+        let end = (pushed + trailer.start).saturating_sub(position);
+        if end < pushed {
+            let kept = end.saturating_sub(self.offset) as usize;
+            self.buffer.truncate_back(kept);
+            self.eof = true;
+        }
+        self.end = Some(end);
+        self.replay_gain = self.find_replay_gain(Some(&trailer));
+        self.trailer.insert(trailer)
+    }
+
+    // Whether more input should be pushed before polling.
+    fn wants_data(&self) -> bool {
+        self.buffer.len() < REFILL_TRIGGER.max(self.tag_wanted)
+    }
+
+    /// The number of input bytes held that haven't been decoded yet.
+    pub(crate) fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Appends input to the buffer. An empty slice marks the end of the input,
+    /// until more data is pushed.
+    pub(crate) fn push(&mut self, mut bytes: &[u8]) {
+        if let Some(end) = self.end {
+            let pushed = self.offset + self.buffer.len() as u64;
+            let left = end.saturating_sub(pushed).min(bytes.len() as u64);
+            bytes = &bytes[..left as usize];
+        }
+        self.eof = bytes.is_empty();
+        self.buffer.extend(bytes.iter());
+    }
+
+    /// Pushes `input`, the bytes the reader just read, and decodes the next
+    /// frame into `pcm`. Returns `Ok(None)` when the reader should read more
+    /// and call again with what it read, and [`Error::Eof`](Error::Eof) if
+    /// the input ended. Called with `None` before reading anything.
+    pub(crate) fn read_frame(
+        &mut self,
+        input: Option<&[u8]>,
+        pcm: &mut [S],
+    ) -> Result<Option<FrameInfo>, Error> {
+        self.read(input, |decoder| decoder.poll_frame(pcm))
+    }
+
+    /// Like [`read_frame`](BufferedDecoder::read_frame), but only parses the
+    /// next frame without decoding it.
+    #[cfg_attr(not(feature = "async_tokio"), allow(dead_code))]
+    pub(crate) fn read_header(&mut self, input: Option<&[u8]>) -> Result<Option<FrameInfo>, Error> {
+        self.read(input, |decoder| decoder.poll(std::ptr::null_mut()))
+    }
+
+    /// Like [`read_frame`](BufferedDecoder::read_frame), but only reads the
+    /// ID3v2 tag at the start of the stream. Returns whether the stream was
+    /// checked for a tag.
+    pub(crate) fn read_tag(&mut self, input: Option<&[u8]>) -> bool {
+        if let Some(bytes) = input {
+            self.push(bytes);
+        }
+        self.poll_tag()
+    }
+
+    fn read<T>(
+        &mut self,
+        input: Option<&[u8]>,
+        poll: impl FnOnce(&mut Self) -> Result<Option<T>, Error>,
+    ) -> Result<Option<T>, Error> {
+        match input {
+            Some(bytes) => self.push(bytes),
+            None if self.wants_data() => return Ok(None),
+            None => {}
+        }
+        poll(self)
+    }
+
+    /// Decodes the next frame into `pcm`. Returns `Ok(None)` if more input is
+    /// needed first, and [`Error::Eof`](Error::Eof) if the input ended.
+    pub(crate) fn poll_frame(&mut self, pcm: &mut [S]) -> Result<Option<FrameInfo>, Error> {
+        assert!(
+            pcm.len() >= MAX_SAMPLES_PER_FRAME,
+            "buffer must hold at least MAX_SAMPLES_PER_FRAME samples"
+        );
+        loop {
+            let info = match self.poll(pcm.as_mut_ptr())? {
+                Some(info) => info,
+                None => return Ok(None),
+            };
+            let info = match self.trim {
+                Some(trim) => match trim.apply(pcm, info) {
+                    Some(info) => info,
+                    None => continue,
+                },
+                None => info,
+            };
+            if let Some(options) = &self.gain {
+                let factor = options.factor(&self.replay_gain);
+                gain::apply(&mut pcm[..info.samples], factor, options.prevent_clipping);
+            }
+            return Ok(Some(info));
+        }
+    }
+
+    // Parses and consumes the ID3v2 tag at the start of the stream, which
+    // is only parsed once all of it was pushed. Returns whether the stream
+    // was checked for a tag, and a tag too large to parse was skipped.
+    fn poll_tag(&mut self) -> bool {
+        if !self.tag_done {
+            let len = self.buffer.len();
+            if len < id3::HEADER_SIZE && !self.eof {
+                return false;
+            }
+            match id3::tag_size(&self.buffer) {
+                Some(size) if size > MAX_TAG_SIZE => self.tag_skipped = size,
+                Some(size) if len < size && !self.eof => {
+                    self.tag_wanted = size;
+                    return false;
+                }
+                Some(size) => {
+                    let size = size.min(len);
+                    self.tag = Tag::parse(&self.buffer[..size]);
+                    self.update_replay_gain();
+                    self.skip_tag_bytes(size);
+                }
+                None => {}
+            }
+            self.tag_done = true;
+            self.tag_wanted = 0;
+        }
+        if self.tag_skipped > 0 {
+            let size = self.tag_skipped.min(self.buffer.len());
+            self.skip_tag_bytes(size);
+            self.tag_skipped -= size;
+            if self.tag_skipped > 0 && !self.eof {
+                return false;
+            }
+            self.tag_skipped = 0;
+        }
+        true
+    }
+
+    fn skip_tag_bytes(&mut self, size: usize) {
+        let len = self.buffer.len();
+        self.buffer.truncate_front(len - size);
+        self.offset += size as u64;
+        // The tag isn't junk before the first frame.
+        self.frame_end = self.offset;
+    }
+
+    fn poll(&mut self, pcm: *mut S) -> Result<Option<FrameInfo>, Error> {
+        if !self.poll_tag() {
+            return Ok(None);
+        }
+        loop {
+            if !self.eof && self.buffer.len() < self.min_buffered {
+                return Ok(None);
+            }
+
+            match self.decode_frame(pcm) {
+                Ok(info) => return Ok(Some(info)),
+                // Skipping always consumes data, so just try again.
+                Err(Error::SkippedData) => {}
+                Err(Error::InsufficientData) if self.eof => return Err(Error::Eof),
+                Err(Error::InsufficientData) => return Ok(None),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    // With a null `pcm` minimp3 only parses the frame header.
+    fn decode_frame(&mut self, pcm: *mut S) -> Result<FrameInfo, Error> {
+        let mut frame_info = unsafe { mem::zeroed() };
+        let samples: usize = unsafe {
+            S::decode_frame(
+                &mut *self.decoder,
+                self.buffer.as_ptr(),
+                self.buffer.len() as _,
+                pcm,
+                &mut frame_info,
+            ) as _
+        };
+
+        let current_len = self.buffer.len();
+        let consumed = frame_info.frame_bytes as usize;
+
+        // minimp3 skips the whole buffer when it ends in an incomplete frame.
+        // Unless the input ended, keep the end around so more input can
+        // complete the frame.
+        if samples == 0 && consumed == current_len && !self.eof {
+            let kept = current_len.min(MAX_PARTIAL_FRAME);
+            self.buffer.truncate_front(kept);
+            self.offset += (current_len - kept) as u64;
+            return Err(Error::InsufficientData);
+        }
+
+        let frame = &self.buffer[frame_info.frame_offset as usize..];
+        let header = FrameHeader::parse(frame);
+        if samples > 0 && self.first_frame.is_none() {
+            self.first_frame = header;
+            self.vbr = VbrHeader::parse(frame);
+            self.update_trim();
+            self.update_replay_gain();
+        }
+        let frame_offset = self.offset + frame_info.frame_offset as u64;
+        self.buffer.truncate_front(current_len - consumed);
+        self.offset += consumed as u64;
+
+        if samples == 0 {
+            return if frame_info.frame_bytes > 0 {
+                Err(Error::SkippedData)
+            } else {
+                Err(Error::InsufficientData)
+            };
+        }
+
+        let samples = samples * frame_info.channels as usize;
+        let mut info = FrameInfo::from_ffi(&frame_info, samples);
+        info.offset = Some(frame_offset);
+        info.header = header;
+        // Also counts frames which were found but couldn't be decoded.
+        info.skipped_bytes = (frame_offset - self.frame_end) as usize;
+        info.sample_position = self.sample_position;
+
+        self.frame_end = frame_offset + info.frame_bytes as u64;
+        self.sample_position += samples as u64;
+        Ok(info)
+    }
+
+    // Takes the internal PCM buffer, so it can be decoded into while the
+    // decoder is borrowed mutably. Must be given back with `put_pcm`.
+    pub(crate) fn take_pcm(&mut self) -> Vec<S> {
+        let mut pcm = mem::take(&mut self.pcm);
+        pcm.resize(MAX_SAMPLES_PER_FRAME, S::default());
+        pcm
+    }
+
+    pub(crate) fn put_pcm(
+        &mut self,
+        pcm: Vec<S>,
+        result: Result<FrameInfo, Error>,
+    ) -> Result<FrameRef<'_, S>, Error> {
+        self.pcm = pcm;
+        let info = result?;
+        Ok(FrameRef {
+            data: &self.pcm[..info.samples],
+            info,
+        })
+    }
+}
@@ -0,0 +1,153 @@
+use crate::{
+    id3::Tag, into_frame, trailer::Trailer, Decoder, Error, Frame, FrameInfo, FrameRef, Sample,
+    MAX_SAMPLES_PER_FRAME,
+};
+use futures_io::{AsyncRead, AsyncSeek};
+use std::{
+    future::poll_fn,
+    io::{self, SeekFrom},
+    pin::Pin,
+};
+
+/// Wraps a [`futures_io::AsyncRead`] reader (async-std, smol, ...) so a
+/// [`Decoder`](Decoder) can decode from it with
+/// [`next_frame_future`](Decoder::next_frame_future), and read its tags
+/// with [`read_tag_future`](Decoder::read_tag_future) and
+/// [`read_trailer_future`](Decoder::read_trailer_future).
+///
+/// Tokio readers don't need it, they are passed to the decoder as they are.
+///
+/// ```no_run
+/// use minimp3_fixed::{Decoder, FuturesIo};
+///
+/// # async fn decode(file: impl futures::io::AsyncRead + Unpin) {
+/// let mut decoder = Decoder::new(FuturesIo::new(file));
+/// while let Ok(frame) = decoder.next_frame_future().await {
+///     println!("Decoded {} samples", frame.data.len());
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FuturesIo<R>(R);
+
+impl<R> FuturesIo<R> {
+    /// Wraps `reader`.
+    pub fn new(reader: R) -> Self {
+        FuturesIo(reader)
+    }
+
+    /// Borrows the wrapped reader.
+    pub fn get_ref(&self) -> &R {
+        &self.0
+    }
+
+    /// Mutably borrows the wrapped reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.0
+    }
+
+    /// Returns the wrapped reader.
+    pub fn into_inner(self) -> R {
+        self.0
+    }
+}
+
+impl<R: AsyncRead + Unpin, S: Sample> Decoder<FuturesIo<R>, S> {
+    /// Reads the ID3v2 tag at the start of the stream, if it has one, before
+    /// decoding any frame.
+    pub async fn read_tag_future(&mut self) -> Result<Option<&Tag>, Error> {
+        let mut input = None;
+        while !self.decoder.read_tag(input) {
+            let read_bytes = read(&mut self.reader.0, &mut self.buffer_refill[..]).await?;
+            input = Some(&self.buffer_refill[..read_bytes]);
+        }
+        Ok(self.decoder.tag())
+    }
+
+    /// Reads a new frame from the internal reader. Returns a [`Frame`](Frame)
+    /// if one was found, or, otherwise, an `Err` explaining why not.
+    pub async fn next_frame_future(&mut self) -> Result<Frame<S>, Error> {
+        let mut pcm = vec![S::default(); MAX_SAMPLES_PER_FRAME];
+        let info = self.next_frame_into_future(&mut pcm).await?;
+        Ok(into_frame(pcm, info))
+    }
+
+    /// Like [`next_frame_future`](Decoder::next_frame_future), but borrows the
+    /// audio from a buffer owned by the decoder instead of allocating.
+    pub async fn next_frame_ref_future(&mut self) -> Result<FrameRef<'_, S>, Error> {
+        let mut pcm = self.decoder.take_pcm();
+        let result = self.next_frame_into_future(&mut pcm).await;
+        self.decoder.put_pcm(pcm, result)
+    }
+
+    /// Like [`next_frame_future`](Decoder::next_frame_future), but decodes
+    /// into `buf`. The decoded samples are `buf[..info.samples]`.
+    ///
+    /// Panics if `buf` is shorter than
+    /// [`MAX_SAMPLES_PER_FRAME`](MAX_SAMPLES_PER_FRAME).
+    pub async fn next_frame_into_future(&mut self, buf: &mut [S]) -> Result<FrameInfo, Error> {
+        let mut input = None;
+        loop {
+            if let Some(info) = self.decoder.read_frame(input, buf)? {
+                return Ok(info);
+            }
+            let read_bytes = read(&mut self.reader.0, &mut self.buffer_refill[..]).await?;
+            input = Some(&self.buffer_refill[..read_bytes]);
+        }
+    }
+}
+
+impl<R, S> Decoder<FuturesIo<R>, S>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+    S: Sample,
+{
+    /// Like [`read_trailer`](Decoder::read_trailer), for async readers.
+    pub async fn read_trailer_future(&mut self) -> Result<&Trailer, Error> {
+        let reader = &mut self.reader.0;
+        let position = seek(reader, SeekFrom::Current(0)).await?;
+        let trailer = read_trailer(reader).await?;
+        seek(reader, SeekFrom::Start(position)).await?;
+        Ok(self.decoder.set_trailer(trailer, position))
+    }
+}
+
+// `futures_io` has no `AsyncReadExt`, that lives in the `futures` crate.
+async fn read<R: AsyncRead + Unpin>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    poll_fn(|cx| Pin::new(&mut *reader).poll_read(cx, buf)).await
+}
+
+async fn read_exact<R: AsyncRead + Unpin>(reader: &mut R, mut buf: &mut [u8]) -> io::Result<()> {
+    while !buf.is_empty() {
+        match read(reader, buf).await {
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(len) => buf = &mut buf[len..],
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+async fn seek<R: AsyncSeek + Unpin>(reader: &mut R, pos: SeekFrom) -> io::Result<u64> {
+    poll_fn(|cx| Pin::new(&mut *reader).poll_seek(cx, pos)).await
+}
+
+// The `futures_io` counterpart of `async_seek::read_trailer`.
+async fn read_trailer<R>(reader: &mut R) -> io::Result<Trailer>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+{
+    let len = seek(reader, SeekFrom::End(0)).await?;
+    let mut tail = Vec::new();
+    loop {
+        match Trailer::step(&tail, len) {
+            Ok(trailer) => return Ok(trailer),
+            Err(start) => {
+                tail = vec![0; (len - start) as usize];
+                seek(reader, SeekFrom::Start(start)).await?;
+                read_exact(reader, &mut tail).await?;
+            }
+        }
+    }
+}
@@ -33,6 +33,19 @@ pub enum Error {
     /// Minimp3 returned an unknown error code
     #[error("Minimp3 unknown error")]
     MiniUnknown,
+    /// The stream has no chapter with the given index.
+    #[error("No such chapter")]
+    NoSuchChapter,
+    /// The saved seek index is of another stream, or of an older version of
+    /// it.
+    #[error("Seek index does not match the stream")]
+    StaleIndex,
+    /// The seek index has frames out of order.
+    #[error("Seek index frames out of order")]
+    InvalidIndex,
+    /// The progress callback cancelled decoding.
+    #[error("Decoding cancelled")]
+    Cancelled,
 }
 
 pub fn from_mini_error(ec: i32) -> Result<(), Error> {
@@ -0,0 +1,201 @@
+use crate::{
+    error::from_mini_error, ffi, header::FrameHeader, id3, read_callback, samples_per_frame,
+    seek_callback, trailer::Trailer, Bounded, Error, FrameInfo,
+};
+
+use std::{
+    any::Any,
+    io::{self, Cursor, Read, Seek},
+    ops::ControlFlow,
+    os::raw::{c_int, c_void},
+    panic::{self, AssertUnwindSafe},
+};
+
+/// A frame found by [`iterate_frames`](iterate_frames), without decoding it.
+#[derive(Debug, Clone, Copy)]
+pub struct RawFrame<'a> {
+    /// The bytes of the frame, header included.
+    pub data: &'a [u8],
+    /// The size of the frames of a free format stream, which have no
+    /// bitrate in their header. Zero for other streams.
+    pub free_format_bytes: usize,
+    /// The header fields of the frame. `offset` is always set, `samples` is
+    /// the number of samples the frame decodes to, and `skipped_bytes`
+    /// counts the bytes since the end of the previous frame or the ID3v2
+    /// tag.
+    pub info: FrameInfo,
+}
+
+/// Walks the frames of `reader`, handing each to `callback` without decoding
+/// it, until the end of the stream or until `callback` returns
+/// `ControlFlow::Break`.
+///
+/// The ID3v2 tag at the start of the stream is skipped and the tags at its
+/// end aren't read. The Xing/Info frame is handed out like the others.
+/// Panics in `callback` are resumed once minimp3 returned.
+///
+/// ```no_run
+/// use std::ops::ControlFlow;
+///
+/// let file = std::fs::File::open("file.mp3").unwrap();
+/// let mut offsets = Vec::new();
+/// minimp3_fixed::iterate_frames(file, |frame| {
+///     offsets.push(frame.info.offset.unwrap());
+///     ControlFlow::Continue(())
+/// })
+/// .unwrap();
+/// ```
+pub fn iterate_frames<R, F>(mut reader: R, mut callback: F) -> Result<(), Error>
+where
+    R: Read + Seek,
+    F: FnMut(RawFrame<'_>) -> ControlFlow<()>,
+{
+    let trailer = Trailer::read(&mut reader)?;
+    reader.seek(io::SeekFrom::Start(0))?;
+    let mut header = [0; id3::HEADER_SIZE];
+    let len = id3::read_full(&mut reader, &mut header)?;
+    let tag_size = id3::tag_size(&header[..len]).unwrap_or(0);
+    reader.seek(io::SeekFrom::Start(0))?;
+    let mut reader = Box::new(Bounded {
+        inner: reader,
+        position: 0,
+        end: trailer.start,
+    });
+
+    let reader_ptr = &mut *reader as *mut Bounded<R>;
+    let mut io = ffi::mp3dec_io_t {
+        read: Some(read_callback::<Bounded<R>>),
+        read_data: reader_ptr as *mut c_void,
+        seek: Some(seek_callback::<Bounded<R>>),
+        seek_data: reader_ptr as *mut c_void,
+    };
+    let mut context = Context::new(&mut callback, tag_size);
+    let mut buf = vec![0u8; ffi::MINIMP3_BUF_SIZE as usize];
+    let res = unsafe {
+        ffi::mp3dec_iterate_cb(
+            &mut io,
+            buf.as_mut_ptr(),
+            buf.len() as ffi::size_t,
+            Some(iterate_callback),
+            &mut context as *mut Context<'_> as *mut c_void,
+        )
+    };
+    context.finish(res)
+}
+
+/// Like [`iterate_frames`](iterate_frames), walking the frames of a stream
+/// in memory. The frames borrow from `data`.
+pub fn iterate_frames_buf<'a, F>(data: &'a [u8], mut callback: F) -> Result<(), Error>
+where
+    F: FnMut(RawFrame<'a>) -> ControlFlow<()>,
+{
+    let trailer = Trailer::read(&mut Cursor::new(data))?;
+    let data = &data[..(trailer.start as usize).min(data.len())];
+    let tag_size = id3::tag_size(data).unwrap_or(0);
+
+    let mut callback = |frame: RawFrame<'_>| {
+        // minimp3 hands out pointers into `data`.
+        let start = frame.data.as_ptr() as usize - data.as_ptr() as usize;
+        callback(RawFrame {
+            data: &data[start..start + frame.data.len()],
+            ..frame
+        })
+    };
+    let mut context = Context::new(&mut callback, tag_size);
+    let res = unsafe {
+        ffi::mp3dec_iterate_buf(
+            data.as_ptr(),
+            data.len() as ffi::size_t,
+            Some(iterate_callback),
+            &mut context as *mut Context<'_> as *mut c_void,
+        )
+    };
+    context.finish(res)
+}
+
+// The user data of the iterate callback.
+struct Context<'a> {
+    callback: &'a mut dyn FnMut(RawFrame<'_>) -> ControlFlow<()>,
+    // The end of the previous frame, or the ID3v2 tag minimp3 skipped.
+    next_offset: u64,
+    sample_position: u64,
+    stopped: bool,
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+impl<'a> Context<'a> {
+    fn new(callback: &'a mut dyn FnMut(RawFrame<'_>) -> ControlFlow<()>, tag_size: usize) -> Self {
+        Context {
+            callback,
+            next_offset: tag_size as u64,
+            sample_position: 0,
+            stopped: false,
+            panic: None,
+        }
+    }
+
+    fn finish(self, res: c_int) -> Result<(), Error> {
+        if let Some(payload) = self.panic {
+            panic::resume_unwind(payload);
+        }
+        if self.stopped {
+            return Ok(());
+        }
+        from_mini_error(res)
+    }
+}
+
+unsafe extern "C" fn iterate_callback(
+    user_data: *mut c_void,
+    frame: *const u8,
+    frame_size: c_int,
+    free_format_bytes: c_int,
+    _buf_size: ffi::size_t,
+    offset: u64,
+    info: *mut ffi::mp3dec_frame_info_t,
+) -> c_int {
+    let context = &mut *(user_data as *mut Context<'_>);
+    let data = std::slice::from_raw_parts(frame, frame_size.max(0) as usize);
+    let info = &*info;
+
+    let header = FrameHeader::parse(data);
+    let channels = info.channels.max(0) as usize;
+    let layer = info.layer.max(0) as usize;
+    let per_channel = match header {
+        Some(header) => header.samples_per_frame(),
+        None => samples_per_frame(layer, info.hz),
+    };
+    let frame_info = FrameInfo {
+        samples: per_channel * channels,
+        sample_rate: info.hz,
+        channels,
+        layer,
+        bitrate: info.bitrate_kbps,
+        offset: Some(offset),
+        frame_bytes: data.len(),
+        skipped_bytes: offset.saturating_sub(context.next_offset) as usize,
+        header,
+        sample_position: context.sample_position,
+    };
+    context.next_offset = offset + data.len() as u64;
+    context.sample_position += frame_info.samples as u64;
+
+    let frame = RawFrame {
+        data,
+        free_format_bytes: free_format_bytes.max(0) as usize,
+        info: frame_info,
+    };
+    // Unwinding into C is undefined behaviour, so a panic stops the
+    // iteration and is resumed once minimp3 returned.
+    match panic::catch_unwind(AssertUnwindSafe(|| (context.callback)(frame))) {
+        Ok(ControlFlow::Continue(())) => 0,
+        Ok(ControlFlow::Break(())) => {
+            context.stopped = true;
+            1
+        }
+        Err(payload) => {
+            context.panic = Some(payload);
+            1
+        }
+    }
+}
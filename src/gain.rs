@@ -0,0 +1,193 @@
+//! ReplayGain loudness normalisation: finding the track and album gains in
+//! the LAME tag, the `TXXX` frames of ID3v2 tags and the items of APE tags,
+//! and the gain stage the decoders apply them with.
+//!
+//! ```no_run
+//! use minimp3_fixed::gain::GainOptions;
+//! use minimp3_fixed::Decoder;
+//!
+//! let file = std::fs::File::open("file.mp3").unwrap();
+//! let mut decoder = Decoder::new(file);
+//! decoder.set_replay_gain(Some(GainOptions::default()));
+//! while let Ok(frame) = decoder.next_frame() {
+//!     // `frame.data` is normalised
+//! }
+//! ```
+
+use crate::{ape::ApeTag, id3::Tag, trailer::Trailer, vbr::LameTag, Sample};
+
+/// The gains in dB which bring a stream to the ReplayGain reference
+/// loudness of 89 dB SPL, or -18 LUFS, and the peak amplitudes, 1.0 being
+/// full scale.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ReplayGain {
+    pub track_gain: Option<f32>,
+    pub track_peak: Option<f32>,
+    pub album_gain: Option<f32>,
+    pub album_peak: Option<f32>,
+}
+
+/// Which of the gains of a [`ReplayGain`](ReplayGain) to apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GainMode {
+    /// Every track plays equally loud.
+    Track,
+    /// The tracks of an album keep their loudness relative to each other.
+    Album,
+}
+
+/// How the decoders apply ReplayGain, see
+/// [`Decoder::set_replay_gain`](crate::Decoder::set_replay_gain).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GainOptions {
+    /// The gain to apply. The other one is used if the stream lacks it.
+    pub mode: GainMode,
+    /// Added to the gain, in dB.
+    pub preamp: f32,
+    /// Lowers the gain so the peak of the stream stays at full scale, and
+    /// clamps the scaled `f32` samples to `-1.0..=1.0`. `i16` samples are
+    /// always clamped. Samples are left as they are when the
+    /// [`factor`](GainOptions::factor) is 1.0, which a peak above full scale
+    /// lowers even at 0 dB.
+    pub prevent_clipping: bool,
+}
+
+impl Default for GainOptions {
+    fn default() -> Self {
+        GainOptions {
+            mode: GainMode::Track,
+            preamp: 0.0,
+            prevent_clipping: true,
+        }
+    }
+}
+
+impl GainOptions {
+    /// The factor to multiply the samples of a stream with the given gains
+    /// with. 1.0 if the stream has no gain.
+    pub fn factor(&self, gain: &ReplayGain) -> f32 {
+        let track = (gain.track_gain, gain.track_peak);
+        let album = (gain.album_gain, gain.album_peak);
+        let (first, second) = match self.mode {
+            GainMode::Track => (track, album),
+            GainMode::Album => (album, track),
+        };
+        let (db, peak) = match (first, second) {
+            ((Some(db), peak), _) | (_, (Some(db), peak)) => (db, peak),
+            _ => return 1.0,
+        };
+
+        let factor = 10f32.powf((db + self.preamp) / 20.0);
+        match peak {
+            Some(peak) if self.prevent_clipping && peak > 0.0 => factor.min(1.0 / peak),
+            _ => factor,
+        }
+    }
+}
+
+impl ReplayGain {
+    /// The gains of a LAME tag, whose peak is the track peak.
+    pub fn from_lame(lame: &LameTag) -> Self {
+        ReplayGain {
+            track_gain: lame.track_gain,
+            track_peak: lame.peak,
+            album_gain: lame.album_gain,
+            album_peak: None,
+        }
+    }
+
+    /// The gains of the `REPLAYGAIN_*` or, failing that, `R128_*` user
+    /// defined text frames of an ID3v2 tag.
+    pub fn from_id3(tag: &Tag) -> Self {
+        from_fields(|key| tag.extended_text(key))
+    }
+
+    /// The gains of the `REPLAYGAIN_*` or, failing that, `R128_*` items of an
+    /// APE tag.
+    pub fn from_ape(tag: &ApeTag) -> Self {
+        from_fields(|key| tag.text(key))
+    }
+
+    /// Collects the gains of every tag of a stream. The gains written by
+    /// ReplayGain scanners win over the ones the encoder wrote to the LAME
+    /// tag.
+    pub fn find(tag: Option<&Tag>, trailer: Option<&Trailer>, lame: Option<&LameTag>) -> Self {
+        let appended = trailer.and_then(|trailer| trailer.id3v2.as_ref());
+        let ape = trailer.and_then(|trailer| trailer.ape.as_ref());
+        let mut gain = ReplayGain::default();
+        for tag in tag.into_iter().chain(appended) {
+            gain = gain.or(ReplayGain::from_id3(tag));
+        }
+        if let Some(ape) = ape {
+            gain = gain.or(ReplayGain::from_ape(ape));
+        }
+        if let Some(lame) = lame {
+            gain = gain.or(ReplayGain::from_lame(lame));
+        }
+        gain
+    }
+
+    /// Fills the values missing from `self` with the ones of `other`.
+    pub fn or(self, other: ReplayGain) -> Self {
+        ReplayGain {
+            track_gain: self.track_gain.or(other.track_gain),
+            track_peak: self.track_peak.or(other.track_peak),
+            album_gain: self.album_gain.or(other.album_gain),
+            album_peak: self.album_peak.or(other.album_peak),
+        }
+    }
+
+    /// Whether no value is known.
+    pub fn is_empty(&self) -> bool {
+        *self == ReplayGain::default()
+    }
+}
+
+fn from_fields<'a>(get: impl Fn(&str) -> Option<&'a str>) -> ReplayGain {
+    let gain = |key: &str, r128: &str| {
+        get(key)
+            .and_then(parse_gain)
+            .or_else(|| get(r128).and_then(parse_r128))
+    };
+    let peak = |key: &str| get(key).and_then(parse_number);
+    ReplayGain {
+        track_gain: gain("REPLAYGAIN_TRACK_GAIN", "R128_TRACK_GAIN"),
+        track_peak: peak("REPLAYGAIN_TRACK_PEAK"),
+        album_gain: gain("REPLAYGAIN_ALBUM_GAIN", "R128_ALBUM_GAIN"),
+        album_peak: peak("REPLAYGAIN_ALBUM_PEAK"),
+    }
+}
+
+// Like `-6.20 dB`.
+fn parse_gain(text: &str) -> Option<f32> {
+    let text = text.trim();
+    let number = text
+        .strip_suffix("dB")
+        .or_else(|| text.strip_suffix("db"))
+        .unwrap_or(text);
+    parse_number(number)
+}
+
+// A Q7.8 fixed point gain relative to the EBU R128 reference of -23 LUFS,
+// which is 5 dB below the ReplayGain reference.
+fn parse_r128(text: &str) -> Option<f32> {
+    let gain: i16 = text.trim().parse().ok()?;
+    Some(gain as f32 / 256.0 + 5.0)
+}
+
+fn parse_number(text: &str) -> Option<f32> {
+    text.trim()
+        .parse()
+        .ok()
+        .filter(|number: &f32| number.is_finite())
+}
+
+// Multiplies `samples` by `factor`.
+pub(crate) fn apply<S: Sample>(samples: &mut [S], factor: f32, clamp: bool) {
+    if factor == 1.0 {
+        return;
+    }
+    for sample in samples {
+        *sample = sample.scale(factor, clamp);
+    }
+}
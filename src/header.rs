@@ -0,0 +1,218 @@
+//! Parsing of MPEG audio frame headers, without decoding anything.
+//!
+//! ```
+//! use minimp3_fixed::header::{ChannelMode, FrameHeader, Layer, Version};
+//!
+//! let header = FrameHeader::parse(&[0xFF, 0xFB, 0x90, 0x64]).unwrap();
+//! assert_eq!(header.version, Version::Mpeg1);
+//! assert_eq!(header.layer, Layer::Layer3);
+//! assert_eq!(header.bitrate, Some(128));
+//! assert_eq!(header.sample_rate, 44100);
+//! assert_eq!(header.channel_mode, ChannelMode::JointStereo);
+//! assert_eq!(header.frame_length(), Some(417));
+//! ```
+
+/// The length of a frame header in bytes.
+pub const HEADER_SIZE: usize = 4;
+
+/// The MPEG audio version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Version {
+    /// MPEG-1, 32, 44.1 and 48 kHz.
+    Mpeg1,
+    /// MPEG-2 LSF, 16, 22.05 and 24 kHz.
+    Mpeg2,
+    /// The unofficial MPEG-2.5 extension, 8, 11.025 and 12 kHz.
+    Mpeg25,
+}
+
+/// The MPEG audio layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Layer {
+    Layer1,
+    Layer2,
+    Layer3,
+}
+
+impl Layer {
+    /// The layer as a number, like [`Frame::layer`](crate::Frame::layer).
+    pub fn number(self) -> usize {
+        match self {
+            Layer::Layer1 => 1,
+            Layer::Layer2 => 2,
+            Layer::Layer3 => 3,
+        }
+    }
+}
+
+/// How the channels of a frame are coded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChannelMode {
+    Stereo,
+    /// Stereo, using intensity and/or M/S stereo as given by the mode
+    /// extension.
+    JointStereo,
+    /// Two independent mono channels.
+    DualChannel,
+    Mono,
+}
+
+/// The de-emphasis the decoded audio should get.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Emphasis {
+    None,
+    /// 50/15 µs.
+    Ms50_15,
+    /// CCITT J.17.
+    CcittJ17,
+}
+
+/// A parsed 4 byte MPEG audio frame header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FrameHeader {
+    pub version: Version,
+    pub layer: Layer,
+    /// Whether a 16 bit CRC follows the header.
+    pub crc_protected: bool,
+    /// Bitrate in kbps, `None` for free format streams.
+    pub bitrate: Option<u32>,
+    /// Sample rate in hertz.
+    pub sample_rate: u32,
+    /// Whether the frame has an extra slot (one byte, four for layer I).
+    pub padding: bool,
+    /// The private bit, free for applications to use.
+    pub private: bool,
+    pub channel_mode: ChannelMode,
+    /// The raw mode extension bits. They select intensity and M/S stereo
+    /// for layer III and the intensity stereo bands for layers I and II,
+    /// and only mean something for [`ChannelMode::JointStereo`].
+    pub mode_extension: u8,
+    pub copyright: bool,
+    /// Whether this is the original media, as opposed to a copy.
+    pub original: bool,
+    pub emphasis: Emphasis,
+}
+
+// In kbps, indexed by the bitrate index. Index 0 is free format, 15 is
+// invalid.
+#[rustfmt::skip]
+const BITRATES_MPEG1: [[u32; 15]; 3] = [
+    [0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448],
+    [0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384],
+    [0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320],
+];
+#[rustfmt::skip]
+const BITRATES_MPEG2: [[u32; 15]; 3] = [
+    [0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256],
+    [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+    [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+];
+
+impl FrameHeader {
+    /// Parses the header at the start of `bytes`. Returns `None` if `bytes`
+    /// is shorter than [`HEADER_SIZE`](HEADER_SIZE) or doesn't start with a
+    /// valid header.
+    pub fn parse(bytes: &[u8]) -> Option<FrameHeader> {
+        let header: [u8; HEADER_SIZE] = bytes.get(..HEADER_SIZE)?.try_into().ok()?;
+        let [b0, b1, b2, b3] = header;
+
+        if b0 != 0xFF || b1 & 0xE0 != 0xE0 {
+            return None;
+        }
+        let version = match (b1 >> 3) & 0b11 {
+            0b00 => Version::Mpeg25,
+            0b10 => Version::Mpeg2,
+            0b11 => Version::Mpeg1,
+            _ => return None,
+        };
+        let layer = match (b1 >> 1) & 0b11 {
+            0b01 => Layer::Layer3,
+            0b10 => Layer::Layer2,
+            0b11 => Layer::Layer1,
+            _ => return None,
+        };
+
+        let bitrate_index = (b2 >> 4) as usize;
+        if bitrate_index == 15 {
+            return None;
+        }
+        let bitrates = match version {
+            Version::Mpeg1 => &BITRATES_MPEG1,
+            Version::Mpeg2 | Version::Mpeg25 => &BITRATES_MPEG2,
+        };
+        let bitrate = match bitrates[layer.number() - 1][bitrate_index] {
+            0 => None,
+            bitrate => Some(bitrate),
+        };
+
+        let sample_rate = match (b2 >> 2) & 0b11 {
+            0 => 44100,
+            1 => 48000,
+            2 => 32000,
+            _ => return None,
+        };
+        let sample_rate = match version {
+            Version::Mpeg1 => sample_rate,
+            Version::Mpeg2 => sample_rate / 2,
+            Version::Mpeg25 => sample_rate / 4,
+        };
+
+        let channel_mode = match b3 >> 6 {
+            0b00 => ChannelMode::Stereo,
+            0b01 => ChannelMode::JointStereo,
+            0b10 => ChannelMode::DualChannel,
+            _ => ChannelMode::Mono,
+        };
+        let emphasis = match b3 & 0b11 {
+            0b00 => Emphasis::None,
+            0b01 => Emphasis::Ms50_15,
+            0b11 => Emphasis::CcittJ17,
+            _ => return None,
+        };
+
+        Some(FrameHeader {
+            version,
+            layer,
+            crc_protected: b1 & 1 == 0,
+            bitrate,
+            sample_rate,
+            padding: (b2 >> 1) & 1 == 1,
+            private: b2 & 1 == 1,
+            channel_mode,
+            mode_extension: (b3 >> 4) & 0b11,
+            copyright: (b3 >> 3) & 1 == 1,
+            original: (b3 >> 2) & 1 == 1,
+            emphasis,
+        })
+    }
+
+    /// The number of channels, 1 for mono and 2 otherwise.
+    pub fn channels(&self) -> usize {
+        match self.channel_mode {
+            ChannelMode::Mono => 1,
+            _ => 2,
+        }
+    }
+
+    /// The number of samples per channel the frame decodes to.
+    pub fn samples_per_frame(&self) -> usize {
+        match (self.layer, self.version) {
+            (Layer::Layer1, _) => 384,
+            (Layer::Layer2, _) | (Layer::Layer3, Version::Mpeg1) => 1152,
+            (Layer::Layer3, _) => 576,
+        }
+    }
+
+    /// The length of the whole frame in bytes, header included. `None` for
+    /// free format streams, where it can only be found by searching for the
+    /// next header.
+    pub fn frame_length(&self) -> Option<usize> {
+        let bitrate = self.bitrate? as usize * 1000;
+        let sample_rate = self.sample_rate as usize;
+        let padding = self.padding as usize;
+        Some(match self.layer {
+            Layer::Layer1 => (12 * bitrate / sample_rate + padding) * 4,
+            _ => self.samples_per_frame() / 8 * bitrate / sample_rate + padding,
+        })
+    }
+}
@@ -0,0 +1,1051 @@
+//! Reading of ID3v2.2, ID3v2.3 and ID3v2.4 tags.
+//!
+//! [`Decoder`](crate::Decoder) and [`SeekDecoder`](crate::SeekDecoder) read
+//! the tag at the start of the stream on their own, see
+//! [`Decoder::read_tag`](crate::Decoder::read_tag) and
+//! [`SeekDecoder::tag`](crate::SeekDecoder::tag).
+//!
+//! ```no_run
+//! use minimp3_fixed::id3::Tag;
+//!
+//! let mut file = std::fs::File::open("file.mp3").unwrap();
+//! if let Some(tag) = Tag::read_from(&mut file).unwrap() {
+//!     println!("{:?} by {:?}", tag.title(), tag.artist());
+//! }
+//! ```
+
+use crate::{trailer::Trailer, FrameInfo};
+use std::io::{self, Read, Seek};
+
+/// The size of the header of a tag, and of the footer of ID3v2.4 tags.
+pub const HEADER_SIZE: usize = 10;
+
+pub(crate) const FLAG_UNSYNCHRONISATION: u8 = 0x80;
+pub(crate) const FLAG_EXTENDED_HEADER: u8 = 0x40;
+pub(crate) const FLAG_FOOTER: u8 = 0x10;
+
+/// An ID3v2 tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tag {
+    /// The major version, 2, 3 or 4.
+    pub version: u8,
+    pub revision: u8,
+    pub frames: Vec<Id3Frame>,
+}
+
+/// A frame of an ID3v2 tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Id3Frame {
+    /// The frame id, like `TIT2`. The three letter ids of ID3v2.2 are
+    /// replaced by their ID3v2.3 equivalent where there is one.
+    pub id: String,
+    pub content: Content,
+}
+
+/// The parsed content of an [`Id3Frame`](Id3Frame).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Content {
+    /// A text frame (`T???`). ID3v2.4 frames can hold several values.
+    Text(Vec<String>),
+    /// A user defined text frame (`TXXX`).
+    ExtendedText { description: String, value: String },
+    /// A comment frame (`COMM`).
+    Comment(Comment),
+    /// An unsynchronised lyrics frame (`USLT`).
+    Lyrics(Lyrics),
+    /// A synchronised lyrics frame (`SYLT`).
+    SyncedLyrics(SyncedLyrics),
+    /// A URL frame (`W???`).
+    Url(String),
+    /// A user defined URL frame (`WXXX`).
+    ExtendedUrl { description: String, url: String },
+    /// An attached picture frame (`APIC`).
+    Picture(Picture),
+    /// A chapter frame (`CHAP`).
+    Chapter(Chapter),
+    /// A table of contents frame (`CTOC`).
+    TableOfContents(TableOfContents),
+    /// Any other frame, and frames which are encrypted or couldn't be
+    /// decompressed, as raw bytes.
+    Unknown(Vec<u8>),
+}
+
+/// The content of a comment frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    /// The ISO-639-2 language code, like `eng`.
+    pub language: String,
+    pub description: String,
+    pub text: String,
+}
+
+/// The content of an unsynchronised lyrics frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lyrics {
+    /// The ISO-639-2 language code, like `eng`.
+    pub language: String,
+    pub description: String,
+    pub text: String,
+}
+
+/// The content of a synchronised lyrics frame: lines of text with the time
+/// they start at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncedLyrics {
+    /// The ISO-639-2 language code, like `eng`.
+    pub language: String,
+    pub description: String,
+    /// The unit of the times of the lines.
+    pub timestamp_format: TimestampFormat,
+    /// What the text is: 1 for lyrics, 2 for a transcription, 3 for movement
+    /// names, 4 for events, 5 for chords, 6 for trivia, 7 for web page URLs,
+    /// 8 for image URLs and 0 for anything else.
+    pub content_type: u8,
+    /// The lines, sorted by time. Karaoke lyrics usually split lines into
+    /// syllables, and start each new line with a newline character.
+    pub lines: Vec<SyncedLine>,
+}
+
+/// The unit of the times in [`SyncedLyrics`](SyncedLyrics).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimestampFormat {
+    /// MPEG frames since the start of the stream.
+    MpegFrames,
+    Milliseconds,
+}
+
+/// A line of [`SyncedLyrics`](SyncedLyrics).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncedLine {
+    /// The time the line starts at, in the unit of the
+    /// [`timestamp_format`](SyncedLyrics::timestamp_format).
+    pub time: u32,
+    pub text: String,
+}
+
+/// The content of an attached picture frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Picture {
+    pub mime_type: String,
+    /// What the picture shows, 3 for the front cover. See
+    /// [`PICTURE_TYPES`](PICTURE_TYPES).
+    pub picture_type: u8,
+    pub description: String,
+    pub data: Vec<u8>,
+}
+
+/// The content of a chapter frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chapter {
+    /// The id tables of contents refer to the chapter by.
+    pub element_id: String,
+    /// The start and end of the chapter in milliseconds.
+    pub start_ms: u32,
+    pub end_ms: u32,
+    /// The byte offsets of the first and last frame of the chapter, if given.
+    pub start_offset: Option<u32>,
+    pub end_offset: Option<u32>,
+    /// The frames describing the chapter, usually a `TIT2` title.
+    pub frames: Vec<Id3Frame>,
+}
+
+/// The content of a table of contents frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableOfContents {
+    pub element_id: String,
+    /// Whether this is the root of the tables of contents.
+    pub top_level: bool,
+    /// Whether the children are in playing order.
+    pub ordered: bool,
+    /// The element ids of the chapters and tables of contents below this one.
+    pub children: Vec<String>,
+    /// The frames describing the table of contents, usually a `TIT2` title.
+    pub frames: Vec<Id3Frame>,
+}
+
+impl Chapter {
+    /// The title (`TIT2`) of the chapter.
+    pub fn title(&self) -> Option<&str> {
+        first_text(&self.frames, "TIT2")
+    }
+
+    /// Whether the chapter is playing at `ms` milliseconds.
+    pub fn contains(&self, ms: u64) -> bool {
+        self.start_ms as u64 <= ms && ms < self.end_ms as u64
+    }
+}
+
+impl TableOfContents {
+    /// The title (`TIT2`) of the table of contents.
+    pub fn title(&self) -> Option<&str> {
+        first_text(&self.frames, "TIT2")
+    }
+}
+
+/// The names of the picture types, which APE tags use in their cover art
+/// keys.
+#[rustfmt::skip]
+pub const PICTURE_TYPES: [&str; 21] = [
+    "Other", "Png Icon", "Icon", "Front", "Back", "Leaflet", "Media", "Lead Artist",
+    "Artist", "Conductor", "Band", "Composer", "Lyricist", "Recording Location",
+    "During Recording", "During Performance", "Video Capture", "Fish", "Illustration",
+    "Band Logotype", "Publisher Logotype",
+];
+
+/// The size of the tag ending with `footer`, header and footer included.
+/// Returns `None` if `footer` isn't an ID3v2.4 tag footer.
+pub fn footer_tag_size(footer: &[u8]) -> Option<usize> {
+    parse_header(footer, b"3DI")
+}
+
+/// The size of the tag starting with `header`, header and footer included.
+/// Returns `None` if `header` doesn't start with an ID3v2 tag header.
+pub fn tag_size(header: &[u8]) -> Option<usize> {
+    parse_header(header, b"ID3")
+}
+
+fn parse_header(header: &[u8], magic: &[u8; 3]) -> Option<usize> {
+    let header = header.get(..HEADER_SIZE)?;
+    if &header[..3] != magic || !(2..=4).contains(&header[3]) || header[4] == 0xFF {
+        return None;
+    }
+    let size = syncsafe(&header[6..10])?;
+    let footer = if header[5] & FLAG_FOOTER != 0 {
+        HEADER_SIZE
+    } else {
+        0
+    };
+    Some(HEADER_SIZE + size as usize + footer)
+}
+
+impl Tag {
+    /// Parses the tag at the start of `data`. Returns `None` if `data`
+    /// doesn't start with a tag. Frames which can't be parsed are skipped,
+    /// and a truncated tag keeps the frames before the cut.
+    pub fn parse(data: &[u8]) -> Option<Tag> {
+        let size = tag_size(data)?;
+        let (version, revision, flags) = (data[3], data[4], data[5]);
+        let end = data.len().min(size);
+        let mut body = data.get(HEADER_SIZE..end)?.to_vec();
+        if flags & FLAG_FOOTER != 0 {
+            body.truncate(size - 2 * HEADER_SIZE);
+        }
+
+        // ID3v2.4 unsynchronises each frame instead.
+        if version < 4 && flags & FLAG_UNSYNCHRONISATION != 0 {
+            body = resynchronise(&body);
+        }
+        let mut frames = &body[..];
+        if flags & FLAG_EXTENDED_HEADER != 0 {
+            let skip = match version {
+                // The whole tag is compressed in a way that was never
+                // specified.
+                2 => return Some(Tag::empty(version, revision)),
+                3 => 4 + u32::from_be_bytes(frames.get(..4)?.try_into().ok()?) as usize,
+                _ => syncsafe(frames.get(..4)?)? as usize,
+            };
+            frames = frames.get(skip..).unwrap_or_default();
+        }
+
+        let unsynchronised = flags & FLAG_UNSYNCHRONISATION != 0;
+        Some(Tag {
+            version,
+            revision,
+            frames: parse_frames(version, unsynchronised, frames),
+        })
+    }
+
+    fn empty(version: u8, revision: u8) -> Tag {
+        Tag {
+            version,
+            revision,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Reads the tag at the current position of `reader`. Returns `None`,
+    /// having read up to [`HEADER_SIZE`](HEADER_SIZE) bytes, if there is
+    /// none, or if the stream ends before the tag does.
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Option<Tag>> {
+        let mut data = vec![0; HEADER_SIZE];
+        if read_full(reader, &mut data)? < HEADER_SIZE {
+            return Ok(None);
+        }
+        let size = match tag_size(&data) {
+            Some(size) => size,
+            None => return Ok(None),
+        };
+        // The size comes from the stream, so only what is there is
+        // allocated.
+        reader
+            .take((size - HEADER_SIZE) as u64)
+            .read_to_end(&mut data)?;
+        if data.len() != size {
+            return Ok(None);
+        }
+        Ok(Tag::parse(&data))
+    }
+
+    /// Reads an ID3v2.4 tag with a footer from the end of `reader`, where it
+    /// may be followed by other trailing tags, see
+    /// [`Trailer`](crate::trailer::Trailer). The position of `reader` is left
+    /// anywhere.
+    pub fn read_appended<R: Read + Seek>(reader: &mut R) -> io::Result<Option<Tag>> {
+        Ok(Trailer::read(reader)?.id3v2)
+    }
+
+    /// The first frame with the given id.
+    pub fn get(&self, id: &str) -> Option<&Id3Frame> {
+        self.frames.iter().find(|frame| frame.id == id)
+    }
+
+    /// The first value of the text frame with the given id.
+    pub fn text(&self, id: &str) -> Option<&str> {
+        first_text(&self.frames, id)
+    }
+
+    /// The value of the user defined text frame (`TXXX`) with the given
+    /// description, ignoring case.
+    pub fn extended_text(&self, description: &str) -> Option<&str> {
+        self.frames.iter().find_map(|frame| match &frame.content {
+            Content::ExtendedText {
+                description: found,
+                value,
+            } if found.eq_ignore_ascii_case(description) => Some(value.as_str()),
+            _ => None,
+        })
+    }
+
+    /// The title (`TIT2`).
+    pub fn title(&self) -> Option<&str> {
+        self.text("TIT2")
+    }
+
+    /// The lead artist (`TPE1`).
+    pub fn artist(&self) -> Option<&str> {
+        self.text("TPE1")
+    }
+
+    /// The album (`TALB`).
+    pub fn album(&self) -> Option<&str> {
+        self.text("TALB")
+    }
+
+    /// The comment frames.
+    pub fn comments(&self) -> impl Iterator<Item = &Comment> {
+        self.frames.iter().filter_map(|frame| match &frame.content {
+            Content::Comment(comment) => Some(comment),
+            _ => None,
+        })
+    }
+
+    /// The unsynchronised lyrics frames.
+    pub fn lyrics(&self) -> impl Iterator<Item = &Lyrics> {
+        self.frames.iter().filter_map(|frame| match &frame.content {
+            Content::Lyrics(lyrics) => Some(lyrics),
+            _ => None,
+        })
+    }
+
+    /// The synchronised lyrics frames.
+    pub fn synced_lyrics(&self) -> impl Iterator<Item = &SyncedLyrics> {
+        self.frames.iter().filter_map(|frame| match &frame.content {
+            Content::SyncedLyrics(lyrics) => Some(lyrics),
+            _ => None,
+        })
+    }
+
+    /// The attached picture frames.
+    pub fn pictures(&self) -> impl Iterator<Item = &Picture> {
+        self.frames.iter().filter_map(|frame| match &frame.content {
+            Content::Picture(picture) => Some(picture),
+            _ => None,
+        })
+    }
+
+    /// The table of contents frames.
+    pub fn tables_of_contents(&self) -> impl Iterator<Item = &TableOfContents> {
+        self.frames.iter().filter_map(|frame| match &frame.content {
+            Content::TableOfContents(toc) => Some(toc),
+            _ => None,
+        })
+    }
+
+    /// The chapters in playing order: the order of the top-level table of
+    /// contents if there is one, otherwise the order of their start times.
+    pub fn chapters(&self) -> Vec<&Chapter> {
+        let mut chapters = Vec::new();
+        if let Some(toc) = self.tables_of_contents().find(|toc| toc.top_level) {
+            self.collect_chapters(toc, 0, &mut chapters);
+        }
+        if chapters.is_empty() {
+            chapters.extend(self.frames.iter().filter_map(|frame| match &frame.content {
+                Content::Chapter(chapter) => Some(chapter),
+                _ => None,
+            }));
+            chapters.sort_by_key(|chapter| chapter.start_ms);
+        }
+        chapters
+    }
+
+    // Tables of contents can nest, and broken tags could make them loop.
+    fn collect_chapters<'a>(
+        &'a self,
+        toc: &TableOfContents,
+        depth: usize,
+        chapters: &mut Vec<&'a Chapter>,
+    ) {
+        const MAX_DEPTH: usize = 8;
+
+        for child in &toc.children {
+            let frame = self.frames.iter().find(|frame| match &frame.content {
+                Content::Chapter(chapter) => &chapter.element_id == child,
+                Content::TableOfContents(toc) => &toc.element_id == child,
+                _ => false,
+            });
+            match frame.map(|frame| &frame.content) {
+                Some(Content::Chapter(chapter)) => chapters.push(chapter),
+                Some(Content::TableOfContents(toc)) if depth < MAX_DEPTH => {
+                    self.collect_chapters(toc, depth + 1, chapters)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// The index in [`chapters`](Tag::chapters) of the chapter playing at
+    /// `ms` milliseconds. This collects the chapters on every call;
+    /// [`SeekDecoder`](crate::SeekDecoder) keeps them instead.
+    pub fn chapter_at(&self, ms: u64) -> Option<usize> {
+        self.chapters()
+            .iter()
+            .position(|chapter| chapter.contains(ms))
+    }
+}
+
+/// Finds the line of [`SyncedLyrics`](SyncedLyrics) playing at a sample
+/// position, like the [`sample_position`](crate::Frame::sample_position) of
+/// the frames of a decoder.
+///
+/// ```no_run
+/// use minimp3_fixed::id3::LyricsCursor;
+/// use minimp3_fixed::SeekDecoder;
+///
+/// let file = std::fs::File::open("file.mp3").unwrap();
+/// let mut decoder = SeekDecoder::new(file).unwrap();
+/// let lyrics = decoder.tag().unwrap().synced_lyrics().next().unwrap().clone();
+/// let mut cursor = decoder.lyrics_cursor(lyrics);
+/// while let Ok(frame) = decoder.decode_frame() {
+///     if let Some(line) = cursor.line_at(frame.sample_position) {
+///         println!("{}", line.text);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LyricsCursor {
+    lyrics: SyncedLyrics,
+    sample_rate: u64,
+    channels: u64,
+    samples_per_frame: u64,
+    // The number of lines started at the last position.
+    started: usize,
+}
+
+impl LyricsCursor {
+    /// A cursor over `lyrics` of a stream with the given sample rate in
+    /// hertz, number of channels, and number of samples per channel in each
+    /// MPEG frame, which converts times in MPEG frames.
+    pub fn new(
+        lyrics: SyncedLyrics,
+        sample_rate: u32,
+        channels: usize,
+        samples_per_frame: usize,
+    ) -> Self {
+        LyricsCursor {
+            lyrics,
+            sample_rate: sample_rate.max(1) as u64,
+            channels: channels.max(1) as u64,
+            samples_per_frame: samples_per_frame.max(1) as u64,
+            started: 0,
+        }
+    }
+
+    /// A cursor over `lyrics` of the stream `frame` was decoded from.
+    pub fn for_frame(lyrics: SyncedLyrics, frame: &FrameInfo) -> Self {
+        let sample_rate = frame.sample_rate.max(0) as u32;
+        LyricsCursor::new(
+            lyrics,
+            sample_rate,
+            frame.channels,
+            frame.samples_per_frame(),
+        )
+    }
+
+    /// The lyrics the cursor is over.
+    pub fn lyrics(&self) -> &SyncedLyrics {
+        &self.lyrics
+    }
+
+    /// The line playing at `sample_position`, counting every channel.
+    /// `None` before the first line.
+    ///
+    /// Positions usually only move forward, which is cheapest, but they can
+    /// also jump back after a seek.
+    pub fn line_at(&mut self, sample_position: u64) -> Option<&SyncedLine> {
+        let frame = sample_position / self.channels;
+        let time = match self.lyrics.timestamp_format {
+            TimestampFormat::MpegFrames => frame / self.samples_per_frame,
+            TimestampFormat::Milliseconds => frame * 1000 / self.sample_rate,
+        };
+        let lines = &self.lyrics.lines;
+        let started = |line: &SyncedLine| line.time as u64 <= time;
+        if self.started > 0 && !started(&lines[self.started - 1]) {
+            self.started = lines.partition_point(started);
+        }
+        while self.started < lines.len() && started(&lines[self.started]) {
+            self.started += 1;
+        }
+        lines.get(self.started.checked_sub(1)?)
+    }
+}
+
+fn first_text<'a>(frames: &'a [Id3Frame], id: &str) -> Option<&'a str> {
+    let frame = frames.iter().find(|frame| frame.id == id)?;
+    match &frame.content {
+        Content::Text(values) => values.first().map(String::as_str),
+        _ => None,
+    }
+}
+
+// Reads until `buf` is full or the reader ends.
+pub(crate) fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut read = 0;
+    while read < buf.len() {
+        match reader.read(&mut buf[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(read)
+}
+
+pub(crate) fn syncsafe(bytes: &[u8]) -> Option<u32> {
+    bytes.iter().try_fold(0, |size, &byte| {
+        if byte & 0x80 != 0 {
+            return None;
+        }
+        Some(size << 7 | byte as u32)
+    })
+}
+
+// Undoes unsynchronisation, which inserts a 0 after every 0xFF.
+fn resynchronise(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut after_ff = false;
+    for &byte in data {
+        if !(after_ff && byte == 0) {
+            out.push(byte);
+        }
+        after_ff = byte == 0xFF;
+    }
+    out
+}
+
+fn parse_frames(version: u8, unsynchronised: bool, mut data: &[u8]) -> Vec<Id3Frame> {
+    let mut frames = Vec::new();
+    while let Some((frame, rest)) = parse_frame(version, unsynchronised, data) {
+        frames.push(frame);
+        data = rest;
+    }
+    frames
+}
+
+// Returns the frame at the start of `data` and the data after it. Stops at the
+// padding or the end of the tag.
+fn parse_frame(version: u8, tag_unsynchronised: bool, data: &[u8]) -> Option<(Id3Frame, &[u8])> {
+    let header_len = frame_header_len(version);
+    let (id, size, format) = parse_frame_header(version, data)?;
+    let body = data.get(header_len..header_len + size)?;
+    let rest = &data[header_len + size..];
+
+    let content = match frame_body(version, tag_unsynchronised, format, body) {
+        Some(body) => parse_content(version, &id, &body),
+        None => Content::Unknown(body.to_vec()),
+    };
+    Some((Id3Frame { id, content }, rest))
+}
+
+pub(crate) fn frame_header_len(version: u8) -> usize {
+    if version == 2 {
+        6
+    } else {
+        10
+    }
+}
+
+// Parses the frame header at the start of `data` into the frame id, the size
+// of the frame body and the format flags. `None` at the padding.
+pub(crate) fn parse_frame_header(version: u8, data: &[u8]) -> Option<(String, usize, u8)> {
+    let header = data.get(..frame_header_len(version))?;
+    let id = &header[..if version == 2 { 3 } else { 4 }];
+    if !id
+        .iter()
+        .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+    {
+        return None;
+    }
+    let size = match version {
+        2 => u32::from_be_bytes([0, header[3], header[4], header[5]]),
+        3 => u32::from_be_bytes(header[4..8].try_into().ok()?),
+        _ => syncsafe(&header[4..8])?,
+    } as usize;
+    let id = match version {
+        2 => v22_id(std::str::from_utf8(id).ok()?)
+            .map(str::to_owned)
+            .unwrap_or_else(|| String::from_utf8_lossy(id).into_owned()),
+        _ => String::from_utf8_lossy(id).into_owned(),
+    };
+    let format = if version == 2 { 0 } else { header[9] };
+    Some((id, size, format))
+}
+
+// How a frame body is stored, from its format flags.
+pub(crate) struct FrameFormat {
+    pub(crate) compressed: bool,
+    pub(crate) encrypted: bool,
+    pub(crate) unsynchronised: bool,
+    // The bytes before the data: decompressed size, encryption method, group
+    // and data length.
+    pub(crate) extra: usize,
+}
+
+impl FrameFormat {
+    pub(crate) fn new(version: u8, tag_unsynchronised: bool, format: u8) -> Self {
+        match version {
+            2 => FrameFormat {
+                compressed: false,
+                encrypted: false,
+                unsynchronised: false,
+                extra: 0,
+            },
+            3 => {
+                let (compressed, encrypted, grouped) =
+                    (format & 0x80 != 0, format & 0x40 != 0, format & 0x20 != 0);
+                FrameFormat {
+                    compressed,
+                    encrypted,
+                    unsynchronised: false,
+                    extra: 4 * compressed as usize + encrypted as usize + grouped as usize,
+                }
+            }
+            _ => {
+                let (grouped, encrypted, length) =
+                    (format & 0x40 != 0, format & 0x04 != 0, format & 0x01 != 0);
+                FrameFormat {
+                    compressed: format & 0x08 != 0,
+                    encrypted,
+                    unsynchronised: tag_unsynchronised || format & 0x02 != 0,
+                    extra: grouped as usize + encrypted as usize + 4 * length as usize,
+                }
+            }
+        }
+    }
+}
+
+// Undoes the unsynchronisation and compression of a frame body. `None` for
+// encrypted frames and frames which fail to decompress.
+pub(crate) fn frame_body(
+    version: u8,
+    tag_unsynchronised: bool,
+    format: u8,
+    body: &[u8],
+) -> Option<Vec<u8>> {
+    let format = FrameFormat::new(version, tag_unsynchronised, format);
+    if format.encrypted {
+        return None;
+    }
+    let mut body = body.get(format.extra..)?.to_vec();
+    if format.unsynchronised {
+        body = resynchronise(&body);
+    }
+    if format.compressed {
+        body = miniz_oxide::inflate::decompress_to_vec_zlib(&body).ok()?;
+    }
+    Some(body)
+}
+
+// The ID3v2.3 ids of the ID3v2.2 frames this module parses or which are
+// common.
+fn v22_id(id: &str) -> Option<&'static str> {
+    Some(match id {
+        "TT1" => "TIT1",
+        "TT2" => "TIT2",
+        "TT3" => "TIT3",
+        "TP1" => "TPE1",
+        "TP2" => "TPE2",
+        "TP3" => "TPE3",
+        "TP4" => "TPE4",
+        "TAL" => "TALB",
+        "TCM" => "TCOM",
+        "TCO" => "TCON",
+        "TRK" => "TRCK",
+        "TPA" => "TPOS",
+        "TYE" => "TYER",
+        "TEN" => "TENC",
+        "TXX" => "TXXX",
+        "COM" => "COMM",
+        "WXX" => "WXXX",
+        "PIC" => "APIC",
+        "ULT" => "USLT",
+        "SLT" => "SYLT",
+        _ => return None,
+    })
+}
+
+fn parse_content(version: u8, id: &str, body: &[u8]) -> Content {
+    parse_known(version, id, body).unwrap_or_else(|| Content::Unknown(body.to_vec()))
+}
+
+fn parse_known(version: u8, id: &str, body: &[u8]) -> Option<Content> {
+    // Only URL and chapter frames have no text encoding byte.
+    match id {
+        "CHAP" => return parse_chapter(version, body).map(Content::Chapter),
+        "CTOC" => return parse_toc(version, body).map(Content::TableOfContents),
+        _ if id.starts_with('W') && id != "WXXX" => {
+            return Some(Content::Url(terminated(0, body).0))
+        }
+        _ => {}
+    }
+    let (&encoding, data) = body.split_first()?;
+    Some(match id {
+        "TXXX" => {
+            let (description, value) = terminated(encoding, data);
+            Content::ExtendedText {
+                description,
+                value: decode(encoding, trim_terminator(encoding, value)),
+            }
+        }
+        "WXXX" => {
+            let (description, url) = terminated(encoding, data);
+            Content::ExtendedUrl {
+                description,
+                url: terminated(0, url).0,
+            }
+        }
+        "COMM" => {
+            let language = String::from_utf8_lossy(data.get(..3)?).into_owned();
+            let (description, text) = terminated(encoding, &data[3..]);
+            Content::Comment(Comment {
+                language,
+                description,
+                text: decode(encoding, trim_terminator(encoding, text)),
+            })
+        }
+        "USLT" => {
+            let language = String::from_utf8_lossy(data.get(..3)?).into_owned();
+            let (description, text) = terminated(encoding, &data[3..]);
+            Content::Lyrics(Lyrics {
+                language,
+                description,
+                text: decode(encoding, trim_terminator(encoding, text)),
+            })
+        }
+        "SYLT" => Content::SyncedLyrics(parse_synced_lyrics(encoding, data)?),
+        "APIC" => {
+            let (mut picture, len) = parse_picture_header(version, body)?;
+            picture.data = body[len..].to_vec();
+            Content::Picture(picture)
+        }
+        _ if id.starts_with('T') => {
+            let text = decode(encoding, trim_terminator(encoding, data));
+            Content::Text(text.split('\0').map(str::to_owned).collect())
+        }
+        _ => return None,
+    })
+}
+
+fn parse_chapter(version: u8, body: &[u8]) -> Option<Chapter> {
+    let (element_id, data) = terminated(0, body);
+    let fields: Vec<u32> = data
+        .get(..16)?
+        .chunks_exact(4)
+        .map(|field| u32::from_be_bytes([field[0], field[1], field[2], field[3]]))
+        .collect();
+    // All bits set means the offset isn't given.
+    let offset = |offset| Some(offset).filter(|&offset| offset != u32::MAX);
+    Some(Chapter {
+        element_id,
+        start_ms: fields[0],
+        end_ms: fields[1],
+        start_offset: offset(fields[2]),
+        end_offset: offset(fields[3]),
+        frames: parse_frames(version, false, &data[16..]),
+    })
+}
+
+fn parse_synced_lyrics(encoding: u8, data: &[u8]) -> Option<SyncedLyrics> {
+    let language = String::from_utf8_lossy(data.get(..3)?).into_owned();
+    let timestamp_format = match data.get(3)? {
+        1 => TimestampFormat::MpegFrames,
+        2 => TimestampFormat::Milliseconds,
+        _ => return None,
+    };
+    let content_type = *data.get(4)?;
+    let (description, mut data) = terminated(encoding, &data[5..]);
+    let mut lines = Vec::new();
+    // Each line is a terminated string followed by its time.
+    while !data.is_empty() {
+        let (text, rest) = terminated(encoding, data);
+        let time = match rest.get(..4) {
+            Some(time) => u32::from_be_bytes([time[0], time[1], time[2], time[3]]),
+            None => break,
+        };
+        lines.push(SyncedLine { time, text });
+        data = &rest[4..];
+    }
+    lines.sort_by_key(|line| line.time);
+    Some(SyncedLyrics {
+        language,
+        description,
+        timestamp_format,
+        content_type,
+        lines,
+    })
+}
+
+fn parse_toc(version: u8, body: &[u8]) -> Option<TableOfContents> {
+    let (element_id, data) = terminated(0, body);
+    let (&flags, data) = data.split_first()?;
+    let (&count, mut data) = data.split_first()?;
+    let mut children = Vec::new();
+    for _ in 0..count {
+        let (child, rest) = terminated(0, data);
+        children.push(child);
+        data = rest;
+    }
+    Some(TableOfContents {
+        element_id,
+        top_level: flags & 0x02 != 0,
+        ordered: flags & 0x01 != 0,
+        children,
+        frames: parse_frames(version, false, data),
+    })
+}
+
+// Parses the fields of a picture frame before the image data, returning them
+// with no data and the length of the fields.
+pub(crate) fn parse_picture_header(version: u8, body: &[u8]) -> Option<(Picture, usize)> {
+    let (&encoding, data) = body.split_first()?;
+    // ID3v2.2 has a three letter image format instead of a MIME type.
+    let (mime_type, data) = match version {
+        2 => (v22_mime_type(data.get(..3)?), &data[3..]),
+        _ => terminated(0, data),
+    };
+    let (&picture_type, data) = data.split_first()?;
+    let (description, data) = terminated(encoding, data);
+    let picture = Picture {
+        mime_type,
+        picture_type,
+        description,
+        data: Vec::new(),
+    };
+    Some((picture, body.len() - data.len()))
+}
+
+fn v22_mime_type(format: &[u8]) -> String {
+    match &format.to_ascii_uppercase()[..] {
+        b"JPG" => "image/jpeg".to_owned(),
+        b"PNG" => "image/png".to_owned(),
+        other => format!("image/{}", String::from_utf8_lossy(other).to_lowercase()),
+    }
+}
+
+// Splits a string terminated by a null character off the start of `data`.
+fn terminated(encoding: u8, data: &[u8]) -> (String, &[u8]) {
+    let end = match encoding {
+        1 | 2 => data
+            .chunks_exact(2)
+            .position(|c| c == [0, 0])
+            .map(|i| (i * 2, i * 2 + 2)),
+        _ => data.iter().position(|&b| b == 0).map(|i| (i, i + 1)),
+    };
+    match end {
+        Some((end, next)) => (decode(encoding, &data[..end]), &data[next..]),
+        None => (decode(encoding, data), &[]),
+    }
+}
+
+fn trim_terminator(encoding: u8, data: &[u8]) -> &[u8] {
+    match encoding {
+        1 | 2 => data.strip_suffix(&[0, 0]).unwrap_or(data),
+        _ => data.strip_suffix(&[0]).unwrap_or(data),
+    }
+}
+
+fn decode(encoding: u8, data: &[u8]) -> String {
+    match encoding {
+        // UTF-16 with a byte order mark, and UTF-16BE.
+        1 | 2 => {
+            let (little_endian, data) = match data {
+                [0xFF, 0xFE, rest @ ..] => (true, rest),
+                [0xFE, 0xFF, rest @ ..] => (false, rest),
+                _ => (false, data),
+            };
+            let units = data.chunks_exact(2).map(|c| {
+                let c = [c[0], c[1]];
+                if little_endian {
+                    u16::from_le_bytes(c)
+                } else {
+                    u16::from_be_bytes(c)
+                }
+            });
+            char::decode_utf16(units)
+                .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                .collect()
+        }
+        3 => String::from_utf8_lossy(data).into_owned(),
+        // ISO-8859-1, which maps directly to the first 256 code points.
+        _ => data.iter().map(|&b| b as char).collect(),
+    }
+}
+
+/// The size of an ID3v1 tag.
+pub const V1_SIZE: usize = 128;
+
+/// The size of an Enhanced TAG+ block, which sits right before an ID3v1 tag.
+pub const ENHANCED_SIZE: usize = 227;
+
+/// An ID3v1 or ID3v1.1 tag, from the last 128 bytes of a stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct V1Tag {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub year: String,
+    pub comment: String,
+    /// The track number, only in ID3v1.1 tags.
+    pub track: Option<u8>,
+    /// The index of the genre, see [`genre_name`](V1Tag::genre_name).
+    pub genre: u8,
+    /// The Enhanced TAG+ block before the tag, which gives longer title,
+    /// artist and album fields.
+    pub enhanced: Option<EnhancedTag>,
+}
+
+/// The Enhanced TAG+ extension of an ID3v1 tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnhancedTag {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    /// 1 for slow up to 4 for hardcore, 0 if unset.
+    pub speed: u8,
+    /// A free form genre.
+    pub genre: String,
+    /// The start and end of the music, as `mmm:ss`.
+    pub start_time: String,
+    pub end_time: String,
+}
+
+impl V1Tag {
+    /// Parses an ID3v1 tag from `data`, which is [`V1_SIZE`](V1_SIZE) bytes
+    /// long and starts with `TAG`.
+    pub fn parse(data: &[u8]) -> Option<V1Tag> {
+        let data = data
+            .get(..V1_SIZE)
+            .filter(|data| data.starts_with(b"TAG"))?;
+        let comment = &data[97..127];
+        // ID3v1.1 takes the last two bytes of the comment for the track.
+        let (comment, track) = match comment[28..] {
+            [0, track] if track != 0 => (&comment[..28], Some(track)),
+            _ => (comment, None),
+        };
+        Some(V1Tag {
+            title: v1_string(&data[3..33]),
+            artist: v1_string(&data[33..63]),
+            album: v1_string(&data[63..93]),
+            year: v1_string(&data[93..97]),
+            comment: v1_string(comment),
+            track,
+            genre: data[127],
+            enhanced: None,
+        })
+    }
+
+    /// The title, from the Enhanced TAG+ block if there is one.
+    pub fn title(&self) -> &str {
+        self.enhanced.as_ref().map_or(&self.title, |e| &e.title)
+    }
+
+    /// The artist, from the Enhanced TAG+ block if there is one.
+    pub fn artist(&self) -> &str {
+        self.enhanced.as_ref().map_or(&self.artist, |e| &e.artist)
+    }
+
+    /// The album, from the Enhanced TAG+ block if there is one.
+    pub fn album(&self) -> &str {
+        self.enhanced.as_ref().map_or(&self.album, |e| &e.album)
+    }
+
+    /// The name of the genre, including the Winamp extensions. `None` for
+    /// 255, which means no genre, and other unknown indices.
+    pub fn genre_name(&self) -> Option<&'static str> {
+        GENRES.get(self.genre as usize).copied()
+    }
+}
+
+impl EnhancedTag {
+    /// Parses an Enhanced TAG+ block from `data`, which is
+    /// [`ENHANCED_SIZE`](ENHANCED_SIZE) bytes long and starts with `TAG+`.
+    pub fn parse(data: &[u8]) -> Option<EnhancedTag> {
+        let data = data
+            .get(..ENHANCED_SIZE)
+            .filter(|data| data.starts_with(b"TAG+"))?;
+        Some(EnhancedTag {
+            title: v1_string(&data[4..64]),
+            artist: v1_string(&data[64..124]),
+            album: v1_string(&data[124..184]),
+            speed: data[184],
+            genre: v1_string(&data[185..215]),
+            start_time: v1_string(&data[215..221]),
+            end_time: v1_string(&data[221..227]),
+        })
+    }
+}
+
+// ID3v1 fields are ISO-8859-1, padded with nulls or spaces.
+fn v1_string(data: &[u8]) -> String {
+    let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    decode(0, &data[..end]).trim_end().to_owned()
+}
+
+#[rustfmt::skip]
+const GENRES: [&str; 192] = [
+    "Blues", "Classic Rock", "Country", "Dance", "Disco", "Funk", "Grunge", "Hip-Hop",
+    "Jazz", "Metal", "New Age", "Oldies", "Other", "Pop", "R&B", "Rap",
+    "Reggae", "Rock", "Techno", "Industrial", "Alternative", "Ska", "Death Metal", "Pranks",
+    "Soundtrack", "Euro-Techno", "Ambient", "Trip-Hop", "Vocal", "Jazz+Funk", "Fusion", "Trance",
+    "Classical", "Instrumental", "Acid", "House", "Game", "Sound Clip", "Gospel", "Noise",
+    "AlternRock", "Bass", "Soul", "Punk", "Space", "Meditative", "Instrumental Pop", "Instrumental Rock",
+    "Ethnic", "Gothic", "Darkwave", "Techno-Industrial", "Electronic", "Pop-Folk", "Eurodance", "Dream",
+    "Southern Rock", "Comedy", "Cult", "Gangsta", "Top 40", "Christian Rap", "Pop/Funk", "Jungle",
+    "Native American", "Cabaret", "New Wave", "Psychadelic", "Rave", "Showtunes", "Trailer", "Lo-Fi",
+    "Tribal", "Acid Punk", "Acid Jazz", "Polka", "Retro", "Musical", "Rock & Roll", "Hard Rock",
+    // Winamp extensions
+    "Folk", "Folk-Rock", "National Folk", "Swing", "Fast Fusion", "Bebob", "Latin", "Revival",
+    "Celtic", "Bluegrass", "Avantgarde", "Gothic Rock", "Progressive Rock", "Psychedelic Rock", "Symphonic Rock", "Slow Rock",
+    "Big Band", "Chorus", "Easy Listening", "Acoustic", "Humour", "Speech", "Chanson", "Opera",
+    "Chamber Music", "Sonata", "Symphony", "Booty Bass", "Primus", "Porn Groove", "Satire", "Slow Jam",
+    "Club", "Tango", "Samba", "Folklore", "Ballad", "Power Ballad", "Rhythmic Soul", "Freestyle",
+    "Duet", "Punk Rock", "Drum Solo", "A capella", "Euro-House", "Dance Hall", "Goa", "Drum & Bass",
+    "Club-House", "Hardcore", "Terror", "Indie", "BritPop", "Afro-Punk", "Polsk Punk", "Beat",
+    "Christian Gangsta Rap", "Heavy Metal", "Black Metal", "Crossover", "Contemporary Christian", "Christian Rock", "Merengue", "Salsa",
+    "Thrash Metal", "Anime", "JPop", "Synthpop", "Abstract", "Art Rock", "Baroque", "Bhangra",
+    "Big Beat", "Breakbeat", "Chillout", "Downtempo", "Dub", "EBM", "Eclectic", "Electro",
+    "Electroclash", "Emo", "Experimental", "Garage", "Global", "IDM", "Illbient", "Industro-Goth",
+    "Jam Band", "Krautrock", "Leftfield", "Lounge", "Math Rock", "New Romantic", "Nu-Breakz", "Post-Punk",
+    "Post-Rock", "Psytrance", "Shoegaze", "Space Rock", "Trop Rock", "World Music", "Neoclassical", "Audiobook",
+    "Audio Theatre", "Neue Deutsche Welle", "Podcast", "Indie Rock", "G-Funk", "Dubstep", "Garage Rock", "Psybient",
+];
@@ -0,0 +1,230 @@
+//! Saving the seek index a [`SeekDecoder`](crate::SeekDecoder) builds by
+//! reading the whole stream, to reopen the stream later without reading it
+//! again.
+//!
+//! ```no_run
+//! use minimp3_fixed::index::{IndexCheck, SeekIndex};
+//! use minimp3_fixed::SeekDecoder;
+//! use std::fs::File;
+//!
+//! let mut decoder = SeekDecoder::new(File::open("file.mp3").unwrap()).unwrap();
+//! let index = decoder.seek_index(IndexCheck::Size).unwrap();
+//! std::fs::write("file.mp3.idx", index.to_bytes().unwrap()).unwrap();
+//!
+//! let index = SeekIndex::parse(&std::fs::read("file.mp3.idx").unwrap()).unwrap();
+//! let decoder = SeekDecoder::new_with_index(File::open("file.mp3").unwrap(), &index);
+//! ```
+
+use crate::Error;
+use std::io::{self, Read};
+
+const MAGIC: &[u8; 4] = b"M3SI";
+/// The version of the format [`SeekIndex::to_bytes`](SeekIndex::to_bytes)
+/// writes.
+pub const VERSION: u8 = 1;
+
+const HAS_HASH: u8 = 1;
+
+/// How a saved index is matched to the stream it is reopened with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IndexCheck {
+    /// The stream has the same length.
+    Size,
+    /// The stream has the same length and content hash, which means reading
+    /// all of it, though without decoding.
+    Hash,
+}
+
+/// A frame of the index: where it starts in the stream, and the number of
+/// samples before it, counting every channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IndexFrame {
+    pub sample: u64,
+    pub offset: u64,
+}
+
+/// The seek index of a stream, see
+/// [`SeekDecoder::seek_index`](crate::SeekDecoder::seek_index).
+///
+/// Sample counts include every channel.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SeekIndex {
+    /// The length of the stream in bytes.
+    pub size: u64,
+    /// The [`hash`](hash) of the whole reader, if the stream is checked
+    /// against it.
+    pub hash: Option<u64>,
+    /// The offset of the first audio frame, after the ID3v2 tag and the
+    /// Xing/Info header.
+    pub start_offset: u64,
+    /// The samples trimmed at the start of the stream as given by its LAME
+    /// tag.
+    pub delay: u64,
+    /// The samples trimmed at the end of the stream as given by its LAME
+    /// tag.
+    pub padding: u64,
+    /// The length of the stream, without the trimmed samples.
+    pub samples: u64,
+    /// The length of the stream as detected when it was opened.
+    pub detected_samples: u64,
+    /// Every frame of the stream, in order.
+    pub frames: Vec<IndexFrame>,
+}
+
+impl SeekIndex {
+    /// Parses an index written by [`to_bytes`](SeekIndex::to_bytes). Returns
+    /// `None` if the data is malformed or of another version.
+    pub fn parse(data: &[u8]) -> Option<SeekIndex> {
+        let mut reader = Reader(data.strip_prefix(MAGIC)?);
+        if reader.byte()? != VERSION {
+            return None;
+        }
+        let flags = reader.byte()?;
+        let size = reader.varint()?;
+        let hash = if flags & HAS_HASH != 0 {
+            Some(u64::from_le_bytes(reader.bytes(8)?.try_into().ok()?))
+        } else {
+            None
+        };
+        let start_offset = reader.varint()?;
+        let delay = reader.varint()?;
+        let padding = reader.varint()?;
+        let samples = reader.varint()?;
+        let detected_samples = reader.varint()?;
+
+        let count = reader.varint()?;
+        // Every frame takes at least two bytes.
+        if count > (reader.0.len() / 2) as u64 {
+            return None;
+        }
+        let mut frames = Vec::with_capacity(count as usize);
+        let mut last = IndexFrame {
+            sample: 0,
+            offset: start_offset,
+        };
+        for _ in 0..count {
+            last = IndexFrame {
+                sample: last.sample.checked_add(reader.varint()?)?,
+                offset: last.offset.checked_add(reader.varint()?)?,
+            };
+            frames.push(last);
+        }
+        if !reader.0.is_empty() {
+            return None;
+        }
+
+        Some(SeekIndex {
+            size,
+            hash,
+            start_offset,
+            delay,
+            padding,
+            samples,
+            detected_samples,
+            frames,
+        })
+    }
+
+    /// Writes the index in a compact binary format, starting with a magic
+    /// number and the [`VERSION`](VERSION). The frames are stored as the
+    /// differences to the previous one, which take about four bytes.
+    ///
+    /// Fails with [`Error::InvalidIndex`](Error::InvalidIndex) if a frame
+    /// starts before the one preceding it, or before `start_offset`.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut data = MAGIC.to_vec();
+        data.push(VERSION);
+        data.push(if self.hash.is_some() { HAS_HASH } else { 0 });
+        write_varint(&mut data, self.size);
+        if let Some(hash) = self.hash {
+            data.extend(hash.to_le_bytes());
+        }
+        for value in [
+            self.start_offset,
+            self.delay,
+            self.padding,
+            self.samples,
+            self.detected_samples,
+            self.frames.len() as u64,
+        ] {
+            write_varint(&mut data, value);
+        }
+
+        let mut last = IndexFrame {
+            sample: 0,
+            offset: self.start_offset,
+        };
+        for frame in &self.frames {
+            let sample = frame.sample.checked_sub(last.sample);
+            let offset = frame.offset.checked_sub(last.offset);
+            let (Some(sample), Some(offset)) = (sample, offset) else {
+                return Err(Error::InvalidIndex);
+            };
+            write_varint(&mut data, sample);
+            write_varint(&mut data, offset);
+            last = *frame;
+        }
+        Ok(data)
+    }
+}
+
+/// The 64 bit FNV-1a hash of everything `reader` reads, the content hash of
+/// [`IndexCheck::Hash`](IndexCheck::Hash). Not meant to resist deliberate
+/// collisions.
+pub fn hash<R: Read>(mut reader: R) -> io::Result<u64> {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        let len = match reader.read(&mut buf) {
+            Ok(0) => return Ok(hash),
+            Ok(len) => len,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        for &byte in &buf[..len] {
+            hash = (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+// LEB128
+fn write_varint(data: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        data.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    data.push(value as u8);
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(bytes)
+    }
+
+    fn byte(&mut self) -> Option<u8> {
+        Some(self.bytes(1)?[0])
+    }
+
+    fn varint(&mut self) -> Option<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            let bits = (byte & 0x7F) as u64;
+            if bits << shift >> shift != bits {
+                return None;
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{Decoder, Error, Frame, Sample, SeekDecoder};
+use std::io::{Read, Seek};
+
+/// Iterates over the frames of the reader, ending at
+/// [`Error::Eof`](Error::Eof).
+impl<R: Read, S: Sample> Iterator for Decoder<R, S> {
+    type Item = Result<Frame<S>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_frame() {
+            Err(Error::Eof) => None,
+            result => Some(result),
+        }
+    }
+}
+
+/// Iterates over the remaining frames, ending at [`Error::Eof`](Error::Eof).
+impl<R: Read + Seek, S: Sample> Iterator for SeekDecoder<R, S> {
+    type Item = Result<Frame<S>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.decode_frame() {
+            Err(Error::Eof) => None,
+            result => Some(result),
+        }
+    }
+}
+
+impl<R: Read, S: Sample> Decoder<R, S> {
+    /// Iterates over the decoded samples instead of frames. Channels are
+    /// interleaved.
+    pub fn samples(&mut self) -> Samples<&mut Self, S> {
+        Samples::new(self)
+    }
+}
+
+impl<R: Read + Seek, S: Sample> SeekDecoder<R, S> {
+    /// Iterates over the decoded samples instead of frames. Channels are
+    /// interleaved.
+    pub fn samples(&mut self) -> Samples<&mut Self, S> {
+        Samples::new(self)
+    }
+}
+
+/// An iterator over the samples of a frame iterator, created by
+/// [`Decoder::samples`](Decoder::samples) and
+/// [`SeekDecoder::samples`](SeekDecoder::samples).
+pub struct Samples<I, S> {
+    frames: I,
+    frame: std::vec::IntoIter<S>,
+}
+
+impl<I, S> Samples<I, S> {
+    fn new(frames: I) -> Self {
+        Samples {
+            frames,
+            frame: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl<I, S> Iterator for Samples<I, S>
+where
+    I: Iterator<Item = Result<Frame<S>, Error>>,
+{
+    type Item = Result<S, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(sample) = self.frame.next() {
+                return Some(Ok(sample));
+            }
+            match self.frames.next()? {
+                Ok(frame) => self.frame = frame.data.into_iter(),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async_tokio")]
+mod stream {
+    use crate::{Decoder, Error, Frame, FrameInfo, Sample};
+    use futures_core::Stream;
+    use std::{
+        pin::Pin,
+        task::{Context, Poll},
+    };
+    use tokio::io::{AsyncRead, ReadBuf};
+
+    /// Streams the frames of the reader, ending at [`Error::Eof`](Error::Eof).
+    impl<R: AsyncRead + Unpin, S: Sample> Stream for Decoder<R, S> {
+        type Item = Result<Frame<S>, Error>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = self.get_mut();
+            // Decodes into the decoder's buffer so pending polls don't
+            // allocate, and only copies out the finished frame.
+            let mut pcm = this.decoder.take_pcm();
+            match this.poll_frame_into(cx, &mut pcm) {
+                Poll::Ready(result) => Poll::Ready(match this.decoder.put_pcm(pcm, result) {
+                    Ok(frame) => Some(Ok(frame.to_frame())),
+                    Err(Error::Eof) => None,
+                    Err(e) => Some(Err(e)),
+                }),
+                Poll::Pending => {
+                    // Only gives the buffer back.
+                    let _ = this.decoder.put_pcm(pcm, Err(Error::InsufficientData));
+                    Poll::Pending
+                }
+            }
+        }
+    }
+
+    impl<R: AsyncRead + Unpin, S: Sample> Decoder<R, S> {
+        fn poll_frame_into(
+            &mut self,
+            cx: &mut Context<'_>,
+            pcm: &mut [S],
+        ) -> Poll<Result<FrameInfo, Error>> {
+            let mut input = None;
+            loop {
+                if let Some(info) = self.decoder.read_frame(input, pcm)? {
+                    return Poll::Ready(Ok(info));
+                }
+                let mut buf = ReadBuf::new(&mut self.buffer_refill[..]);
+                let read_bytes = match Pin::new(&mut self.reader).poll_read(cx, &mut buf) {
+                    Poll::Ready(Ok(())) => buf.filled().len(),
+                    Poll::Ready(Err(e)) => return Poll::Ready(Err(e.into())),
+                    Poll::Pending => return Poll::Pending,
+                };
+                input = Some(&self.buffer_refill[..read_bytes]);
+            }
+        }
+    }
+}
@@ -5,50 +5,131 @@
 //! ## Tokio
 //!
 //! By enabling the feature flag `async_tokio` you can decode frames using async
-//! IO and tokio.
+//! IO and tokio, either one at a time or as a `futures_core::Stream`.
+//! [`AsyncSeekDecoder`](AsyncSeekDecoder) is the async counterpart of
+//! [`SeekDecoder`](SeekDecoder).
+//!
+//! ## futures-io
+//!
+//! The `async_futures` feature flag does the same for readers implementing
+//! `futures_io::AsyncRead` (async-std, smol), wrapped in
+//! [`FuturesIo`](FuturesIo).
+//!
+//! ## Without a reader
+//!
+//! [`PushDecoder`](PushDecoder) is fed bytes as they arrive instead of
+//! reading them, for input coming from callbacks.
+//!
+//! ## Whole streams
+//!
+//! [`decode_all`](decode_all) decodes a whole stream at once, for batch
+//! jobs which don't need to stream the audio.
+//!
+//! ## Frame headers
+//!
+//! The [`header`](header) module parses frame headers in pure Rust, to
+//! inspect a stream without decoding it. The [`vbr`](vbr) module does the
+//! same for the Xing/Info and VBRI headers giving the length of a stream.
+//! [`iterate_frames`](iterate_frames) walks the frames of a stream with
+//! minimp3, without decoding them.
+//!
+//! ## Tags
+//!
+//! The [`id3`](id3) module reads ID3v2 tags. The decoders skip the tag at the
+//! start of the stream and make it available, see
+//! [`Decoder::read_tag`](Decoder::read_tag). The [`trailer`](trailer) module
+//! finds the ID3v1, APE and ID3v2 tags at the end of a stream, before which
+//! decoders of seekable readers stop, see
+//! [`Decoder::read_trailer`](Decoder::read_trailer). The
+//! [`picture`](picture) module finds the cover art in all of them, and
+//! [`LyricsCursor`](id3::LyricsCursor) follows synchronised lyrics as the
+//! stream plays. The [`gain`](gain) module finds their ReplayGain values,
+//! which the decoders can apply, see
+//! [`Decoder::set_replay_gain`](Decoder::set_replay_gain).
+//!
+//! ## Seek indexes
+//!
+//! The [`index`](index) module saves the seek index of a
+//! [`SeekDecoder`](SeekDecoder), so reopening the stream doesn't read all of
+//! it again.
+//!
+//! ## Float output
+//!
+//! The decoders can produce `f32` samples instead of `i16`, see
+//! [`Decoder::new_float`](Decoder::new_float),
+//! [`SeekDecoder::new_float`](SeekDecoder::new_float) and
+//! [`PushDecoder::new_float`](PushDecoder::new_float).
 //!
 //! [See the README for example usages.](https://github.com/germangb/minimp3-rs/tree/async)
 pub use minimp3_sys as ffi;
 
 // use std::mem;
-use std::io::{Read, Seek};
+use std::io::{Cursor, Read, Seek};
 // use std::marker::Send;
 use std::os::raw::{c_int, c_void};
 
-pub use error::Error;
+use buffered::BufferedDecoder;
 use error::from_mini_error;
-use slice_ring_buffer::SliceRingBuffer;
+pub use error::Error;
+pub use frames::{iterate_frames, iterate_frames_buf, RawFrame};
+use gain::{GainOptions, ReplayGain};
+use header::FrameHeader;
+use id3::{Chapter, LyricsCursor, SyncedLyrics, Tag};
+use index::{IndexCheck, IndexFrame, SeekIndex};
+pub use iter::Samples;
+pub use load::{decode_all, DecodeProgress, DecodedAudio};
+pub use push::PushDecoder;
+pub use sample::Sample;
+use std::time::Duration;
 use std::{io, marker::Send, mem};
+use trailer::Trailer;
+use vbr::{LameTag, VbrHeader};
+
+#[cfg(feature = "async_tokio")]
+pub use async_seek::AsyncSeekDecoder;
+#[cfg(feature = "async_futures")]
+pub use compat::FuturesIo;
 
+pub mod ape;
+#[cfg(feature = "async_tokio")]
+mod async_seek;
+mod buffered;
+#[cfg(feature = "async_futures")]
+mod compat;
 mod error;
+mod frames;
+pub mod gain;
+pub mod header;
+pub mod id3;
+pub mod index;
+mod iter;
+mod load;
+pub mod picture;
+mod push;
+mod sample;
+pub mod trailer;
+pub mod vbr;
 
 /// Maximum number of samples present in a MP3 frame.
 pub const MAX_SAMPLES_PER_FRAME: usize = ffi::MINIMP3_MAX_SAMPLES_PER_FRAME as usize;
 
-const BUFFER_SIZE: usize = MAX_SAMPLES_PER_FRAME * 15;
-const REFILL_TRIGGER: usize = MAX_SAMPLES_PER_FRAME * 8;
-
 /// A MP3 decoder which consumes a reader and produces [`Frame`]s.
 ///
+/// The samples are `i16` unless the decoder was created with
+/// [`new_float`](Decoder::new_float).
+///
 /// [`Frame`]: ./struct.Frame.html
-pub struct Decoder<R> {
+pub struct Decoder<R, S = i16> {
     reader: R,
-    buffer: SliceRingBuffer<u8>,
     buffer_refill: Box<[u8; MAX_SAMPLES_PER_FRAME * 5]>,
-    decoder: Box<ffi::mp3dec_t>,
+    decoder: BufferedDecoder<S>,
 }
 
-// Explicitly impl [Send] for [Decoder]s. This isn't a great idea and should
-// probably be removed in the future. The only reason it's here is that
-// [SliceRingBuffer] doesn't implement [Send] (since it uses raw pointers
-// internally), even though it's safe to send it across thread boundaries.
-unsafe impl<R: Send> Send for Decoder<R> {}
-
 /// A MP3 frame, owning the decoded audio of that frame.
 #[derive(Debug, Clone)]
-pub struct Frame {
+pub struct Frame<S = i16> {
     /// The decoded audio held by this frame. Channels are interleaved.
-    pub data: Vec<i16>,
+    pub data: Vec<S>,
     /// This frame's sample rate in hertz.
     pub sample_rate: i32,
     /// The number of channels in this frame.
@@ -57,19 +138,141 @@ pub struct Frame {
     pub layer: usize,
     /// Current bitrate as of this frame, in kb/s.
     pub bitrate: i32,
+    /// Byte offset of the frame header in the stream.
+    pub offset: Option<u64>,
+    /// The size of the encoded frame in bytes, header included.
+    pub frame_bytes: usize,
+    /// The number of bytes before this frame which weren't part of a decoded
+    /// frame, like tags, garbage or frames which couldn't be decoded.
+    pub skipped_bytes: usize,
+    /// The frame header, holding the MPEG version, channel mode and CRC
+    /// presence.
+    pub header: Option<FrameHeader>,
+    /// Index of the first sample of this frame since the start of the
+    /// stream, counting every channel, like
+    /// [`SeekDecoder::seek_samples`](SeekDecoder::seek_samples).
+    pub sample_position: u64,
+}
+
+impl<S> Frame<S> {
+    /// The header fields of this frame.
+    pub fn info(&self) -> FrameInfo {
+        FrameInfo {
+            samples: self.data.len(),
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            layer: self.layer,
+            bitrate: self.bitrate,
+            offset: self.offset,
+            frame_bytes: self.frame_bytes,
+            skipped_bytes: self.skipped_bytes,
+            header: self.header,
+            sample_position: self.sample_position,
+        }
+    }
+}
+
+/// Describes a decoded frame without holding its audio.
+///
+/// Returned by the `next_frame_into` methods, which write the audio to a
+/// buffer provided by the caller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameInfo {
+    /// The number of samples decoded, counting every channel. Channels are
+    /// interleaved.
+    pub samples: usize,
+    /// This frame's sample rate in hertz.
+    pub sample_rate: i32,
+    /// The number of channels in this frame.
+    pub channels: usize,
+    /// MPEG layer used by this file.
+    pub layer: usize,
+    /// Current bitrate as of this frame, in kb/s.
+    pub bitrate: i32,
+    /// Byte offset of the frame header in the stream.
+    pub offset: Option<u64>,
+    /// The size of the encoded frame in bytes, header included.
+    pub frame_bytes: usize,
+    /// The number of bytes before this frame which weren't part of a decoded
+    /// frame, like tags, garbage or frames which couldn't be decoded.
+    pub skipped_bytes: usize,
+    /// The frame header, holding the MPEG version, channel mode and CRC
+    /// presence.
+    pub header: Option<FrameHeader>,
+    /// Index of the first sample of this frame since the start of the
+    /// stream, counting every channel, like
+    /// [`SeekDecoder::seek_samples`](SeekDecoder::seek_samples).
+    pub sample_position: u64,
+}
+
+impl FrameInfo {
+    /// The number of samples per channel a frame of this stream decodes to.
+    /// Taken from the header, or if there is none, the layer and sample
+    /// rate.
+    pub fn samples_per_frame(&self) -> usize {
+        match self.header {
+            Some(header) => header.samples_per_frame(),
+            None => samples_per_frame(self.layer, self.sample_rate),
+        }
+    }
+
+    // The stream position fields are left for the caller to fill in.
+    fn from_ffi(frame_info: &ffi::mp3dec_frame_info_t, samples: usize) -> Self {
+        FrameInfo {
+            samples,
+            sample_rate: frame_info.hz,
+            channels: frame_info.channels as usize,
+            layer: frame_info.layer as usize,
+            bitrate: frame_info.bitrate_kbps,
+            offset: None,
+            // minimp3 counts the skipped bytes as part of the frame.
+            frame_bytes: (frame_info.frame_bytes - frame_info.frame_offset) as usize,
+            skipped_bytes: frame_info.frame_offset as usize,
+            header: None,
+            sample_position: 0,
+        }
+    }
+}
+
+/// A MP3 frame borrowing its decoded audio from the decoder that produced it.
+///
+/// The audio is overwritten by the next call into the decoder, use
+/// [`to_frame`](FrameRef::to_frame) to keep it around.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameRef<'a, S = i16> {
+    /// The decoded audio of this frame. Channels are interleaved.
+    pub data: &'a [S],
+    /// The header fields of this frame.
+    pub info: FrameInfo,
+}
+
+impl<S: Copy> FrameRef<'_, S> {
+    /// Copies the audio into an owned [`Frame`](Frame).
+    pub fn to_frame(&self) -> Frame<S> {
+        into_frame(self.data.to_vec(), self.info)
+    }
 }
 
 impl<R> Decoder<R> {
     /// Creates a new decoder, consuming the `reader`.
     pub fn new(reader: R) -> Self {
-        let mut minidec = unsafe { Box::new(mem::zeroed()) };
-        unsafe { ffi::mp3dec_init(&mut *minidec) }
+        Self::open(reader)
+    }
+}
+
+impl<R> Decoder<R, f32> {
+    /// Creates a new decoder producing `f32` samples, consuming the `reader`.
+    pub fn new_float(reader: R) -> Self {
+        Self::open(reader)
+    }
+}
 
+impl<R, S: Sample> Decoder<R, S> {
+    fn open(reader: R) -> Self {
         Self {
             reader,
-            buffer: SliceRingBuffer::with_capacity(BUFFER_SIZE),
             buffer_refill: Box::new([0; MAX_SAMPLES_PER_FRAME * 5]),
-            decoder: minidec,
+            decoder: BufferedDecoder::new(),
         }
     }
 
@@ -89,122 +292,224 @@ impl<R> Decoder<R> {
         self.reader
     }
 
-    fn decode_frame(&mut self) -> Result<Frame, Error> {
-        let mut frame_info = unsafe { mem::zeroed() };
-        let mut pcm = Vec::with_capacity(MAX_SAMPLES_PER_FRAME);
-        let samples: usize = unsafe {
-            ffi::mp3dec_decode_frame(
-                &mut *self.decoder,
-                self.buffer.as_ptr(),
-                self.buffer.len() as _,
-                pcm.as_mut_ptr(),
-                &mut frame_info,
-            ) as _
-        };
+    /// Trims the samples the encoder added at the start and the end of the
+    /// stream as given by its LAME tag, see
+    /// [`LameTag::trimmed_samples`](LameTag::trimmed_samples), and drops the
+    /// silent Xing/Info frame, like [`SeekDecoder`](SeekDecoder) does. Off by
+    /// default.
+    ///
+    /// Frame sample positions then count from the first sample of audio.
+    pub fn set_gapless(&mut self, gapless: bool) {
+        self.decoder.set_gapless(gapless);
+    }
 
-        if samples > 0 {
-            unsafe {
-                pcm.set_len(samples * frame_info.channels as usize);
-            }
-        }
+    /// Applies ReplayGain to the decoded audio, or stops applying it with
+    /// `None`. Off by default.
+    ///
+    /// The gains are the ones of [`replay_gain`](Decoder::replay_gain) as
+    /// each frame is decoded, so call [`read_trailer`](Decoder::read_trailer)
+    /// first to use the gains of the tags at the end of the stream.
+    pub fn set_replay_gain(&mut self, options: Option<GainOptions>) {
+        self.decoder.set_replay_gain(options);
+    }
 
-        let frame = Frame {
-            data: pcm,
-            sample_rate: frame_info.hz,
-            channels: frame_info.channels as usize,
-            layer: frame_info.layer as usize,
-            bitrate: frame_info.bitrate_kbps,
-        };
+    /// The ReplayGain values found in the tags read so far. The ID3v2 tag
+    /// and the LAME tag are known once the first frame was decoded.
+    pub fn replay_gain(&self) -> ReplayGain {
+        self.decoder.replay_gain()
+    }
 
-        let current_len = self.buffer.len();
-        self.buffer
-            .truncate_front(current_len - frame_info.frame_bytes as usize);
+    /// The Xing/Info or VBRI header of the stream, if it has one. Known once
+    /// the first frame was decoded.
+    pub fn vbr_header(&self) -> Option<&VbrHeader> {
+        self.decoder.vbr_header()
+    }
 
-        if samples == 0 {
-            if frame_info.frame_bytes > 0 {
-                Err(Error::SkippedData)
-            } else {
-                Err(Error::InsufficientData)
-            }
-        } else {
-            Ok(frame)
-        }
+    /// The number of samples of the stream, counting every channel, as given
+    /// by its VBR header. Known once the first frame was decoded. Doesn't
+    /// count the samples trimmed by [`set_gapless`](Decoder::set_gapless).
+    pub fn total_samples(&self) -> Option<u64> {
+        self.decoder.total_samples()
+    }
+
+    /// The ID3v2 tag at the start of the stream, if it has one. Known once
+    /// the first frame was decoded, or after [`read_tag`](Decoder::read_tag).
+    pub fn tag(&self) -> Option<&Tag> {
+        self.decoder.tag()
+    }
+
+    /// The tags at the end of the stream. Only known after
+    /// [`read_trailer`](Decoder::read_trailer).
+    pub fn trailer(&self) -> Option<&Trailer> {
+        self.decoder.trailer()
+    }
+
+    /// The duration of the stream, as given by its VBR header. Known once the
+    /// first frame was decoded.
+    pub fn duration(&self) -> Option<Duration> {
+        let header = self.decoder.first_frame()?;
+        Some(duration(
+            self.total_samples()?,
+            header.channels(),
+            header.sample_rate as i32,
+        ))
+    }
+}
+
+fn duration(samples: u64, channels: usize, sample_rate: i32) -> Duration {
+    if channels == 0 || sample_rate <= 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_secs_f64(samples as f64 / channels as f64 / sample_rate as f64)
+}
+
+// MPEG-2 and 2.5 layer III frames hold half as many samples, and use the
+// lower sample rates.
+fn samples_per_frame(layer: usize, sample_rate: i32) -> usize {
+    match layer {
+        1 => 384,
+        3 if sample_rate < 32000 => 576,
+        _ => 1152,
+    }
+}
+
+fn into_frame<S>(mut pcm: Vec<S>, info: FrameInfo) -> Frame<S> {
+    pcm.truncate(info.samples);
+    Frame {
+        data: pcm,
+        sample_rate: info.sample_rate,
+        channels: info.channels,
+        layer: info.layer,
+        bitrate: info.bitrate,
+        offset: info.offset,
+        frame_bytes: info.frame_bytes,
+        skipped_bytes: info.skipped_bytes,
+        header: info.header,
+        sample_position: info.sample_position,
     }
 }
 
 #[cfg(feature = "async_tokio")]
-impl<R: tokio::io::AsyncRead + std::marker::Unpin> Decoder<R> {
+impl<R: tokio::io::AsyncRead + std::marker::Unpin, S: Sample> Decoder<R, S> {
+    /// Reads the ID3v2 tag at the start of the stream, if it has one, before
+    /// decoding any frame.
+    pub async fn read_tag_future(&mut self) -> Result<Option<&Tag>, Error> {
+        use tokio::io::AsyncReadExt;
+
+        let mut input = None;
+        while !self.decoder.read_tag(input) {
+            let read_bytes = self.reader.read(&mut self.buffer_refill[..]).await?;
+            input = Some(&self.buffer_refill[..read_bytes]);
+        }
+        Ok(self.decoder.tag())
+    }
+
     /// Reads a new frame from the internal reader. Returns a [`Frame`](Frame)
     /// if one was found, or, otherwise, an `Err` explaining why not.
-    pub async fn next_frame_future(&mut self) -> Result<Frame, Error> {
-        loop {
-            // Keep our buffers full
-            let bytes_read = if self.buffer.len() < REFILL_TRIGGER {
-                Some(self.refill_future().await?)
-            } else {
-                None
-            };
+    pub async fn next_frame_future(&mut self) -> Result<Frame<S>, Error> {
+        let mut pcm = vec![S::default(); MAX_SAMPLES_PER_FRAME];
+        let info = self.next_frame_into_future(&mut pcm).await?;
+        Ok(into_frame(pcm, info))
+    }
 
-            match self.decode_frame() {
-                Ok(frame) => return Ok(frame),
-                // Don't do anything if we didn't have enough data or we skipped data,
-                // just let the loop spin around another time.
-                Err(Error::InsufficientData) | Err(Error::SkippedData) => {
-                    // If there are no more bytes to be read from the file, return EOF
-                    if let Some(0) = bytes_read {
-                        return Err(Error::Eof);
-                    }
-                }
-                Err(e) => return Err(e),
+    /// Like [`next_frame_future`](Decoder::next_frame_future), but borrows the
+    /// audio from a buffer owned by the decoder instead of allocating.
+    pub async fn next_frame_ref_future(&mut self) -> Result<FrameRef<'_, S>, Error> {
+        let mut pcm = self.decoder.take_pcm();
+        let result = self.next_frame_into_future(&mut pcm).await;
+        self.decoder.put_pcm(pcm, result)
+    }
+
+    /// Like [`next_frame_future`](Decoder::next_frame_future), but decodes
+    /// into `buf`. The decoded samples are `buf[..info.samples]`.
+    ///
+    /// Panics if `buf` is shorter than
+    /// [`MAX_SAMPLES_PER_FRAME`](MAX_SAMPLES_PER_FRAME).
+    pub async fn next_frame_into_future(&mut self, buf: &mut [S]) -> Result<FrameInfo, Error> {
+        use tokio::io::AsyncReadExt;
+
+        let mut input = None;
+        loop {
+            if let Some(info) = self.decoder.read_frame(input, buf)? {
+                return Ok(info);
             }
+            let read_bytes = self.reader.read(&mut self.buffer_refill[..]).await?;
+            input = Some(&self.buffer_refill[..read_bytes]);
         }
     }
+}
 
-    async fn refill_future(&mut self) -> Result<usize, io::Error> {
-        use tokio::io::AsyncReadExt;
+#[cfg(feature = "async_tokio")]
+impl<R, S> Decoder<R, S>
+where
+    R: tokio::io::AsyncRead + tokio::io::AsyncSeek + std::marker::Unpin,
+    S: Sample,
+{
+    /// Like [`read_trailer`](Decoder::read_trailer), for async readers.
+    pub async fn read_trailer_future(&mut self) -> Result<&Trailer, Error> {
+        use tokio::io::AsyncSeekExt;
 
-        let read_bytes = self.reader.read(&mut self.buffer_refill[..]).await?;
-        self.buffer.extend(self.buffer_refill[..read_bytes].iter());
+        let position = self.reader.stream_position().await?;
+        let trailer = async_seek::read_trailer(&mut self.reader).await?;
+        self.reader.seek(io::SeekFrom::Start(position)).await?;
+        Ok(self.decoder.set_trailer(trailer, position))
+    }
+}
 
-        Ok(read_bytes)
+impl<R: io::Read + Seek, S: Sample> Decoder<R, S> {
+    /// Reads the tags at the end of the stream, and stops decoding where they
+    /// start so they aren't mistaken for frames.
+    pub fn read_trailer(&mut self) -> Result<&Trailer, Error> {
+        let position = self.reader.stream_position()?;
+        let trailer = Trailer::read(&mut self.reader)?;
+        self.reader.seek(io::SeekFrom::Start(position))?;
+        Ok(self.decoder.set_trailer(trailer, position))
     }
 }
 
-// TODO FIXME do something about the code repetition. The only difference is the
-//  use of .await after IO reads...
+impl<R: io::Read, S: Sample> Decoder<R, S> {
+    /// Reads the ID3v2 tag at the start of the stream, if it has one, before
+    /// decoding any frame. Tags larger than 8 MiB are skipped without being
+    /// parsed.
+    pub fn read_tag(&mut self) -> Result<Option<&Tag>, Error> {
+        let mut input = None;
+        while !self.decoder.read_tag(input) {
+            let read_bytes = self.reader.read(&mut self.buffer_refill[..])?;
+            input = Some(&self.buffer_refill[..read_bytes]);
+        }
+        Ok(self.decoder.tag())
+    }
 
-impl<R: io::Read> Decoder<R> {
     /// Reads a new frame from the internal reader. Returns a [`Frame`](Frame)
     /// if one was found, or, otherwise, an `Err` explaining why not.
-    pub fn next_frame(&mut self) -> Result<Frame, Error> {
-        loop {
-            // Keep our buffers full
-            let bytes_read = if self.buffer.len() < REFILL_TRIGGER {
-                Some(self.refill()?)
-            } else {
-                None
-            };
-
-            match self.decode_frame() {
-                Ok(frame) => return Ok(frame),
-                // Don't do anything if we didn't have enough data or we skipped data,
-                // just let the loop spin around another time.
-                Err(Error::InsufficientData) | Err(Error::SkippedData) => {
-                    // If there are no more bytes to be read from the file, return EOF
-                    if let Some(0) = bytes_read {
-                        return Err(Error::Eof);
-                    }
-                }
-                Err(e) => return Err(e),
-            }
-        }
+    pub fn next_frame(&mut self) -> Result<Frame<S>, Error> {
+        let mut pcm = vec![S::default(); MAX_SAMPLES_PER_FRAME];
+        let info = self.next_frame_into(&mut pcm)?;
+        Ok(into_frame(pcm, info))
     }
 
-    fn refill(&mut self) -> Result<usize, io::Error> {
-        let read_bytes = self.reader.read(&mut self.buffer_refill[..])?;
-        self.buffer.extend(self.buffer_refill[..read_bytes].iter());
+    /// Like [`next_frame`](Decoder::next_frame), but borrows the audio from a
+    /// buffer owned by the decoder instead of allocating.
+    pub fn next_frame_ref(&mut self) -> Result<FrameRef<'_, S>, Error> {
+        let mut pcm = self.decoder.take_pcm();
+        let result = self.next_frame_into(&mut pcm);
+        self.decoder.put_pcm(pcm, result)
+    }
 
-        Ok(read_bytes)
+    /// Like [`next_frame`](Decoder::next_frame), but decodes into `buf`. The
+    /// decoded samples are `buf[..info.samples]`.
+    ///
+    /// Panics if `buf` is shorter than
+    /// [`MAX_SAMPLES_PER_FRAME`](MAX_SAMPLES_PER_FRAME).
+    pub fn next_frame_into(&mut self, buf: &mut [S]) -> Result<FrameInfo, Error> {
+        let mut input = None;
+        loop {
+            if let Some(info) = self.decoder.read_frame(input, buf)? {
+                return Ok(info);
+            }
+            let read_bytes = self.reader.read(&mut self.buffer_refill[..])?;
+            input = Some(&self.buffer_refill[..read_bytes]);
+        }
     }
 }
 
@@ -222,10 +527,10 @@ where
     // 0 or less than size for end of stream/file
     while position < size as usize {
         match reader.read(&mut buf[position..]) {
-            Ok(n) if n == 0 => return position as u64,
+            Ok(0) => return position as u64,
             Ok(n) => position += n,
             // -1
-            Err(_) => return std::u64::MAX,
+            Err(_) => return u64::MAX,
         }
     }
     position as u64
@@ -243,117 +548,712 @@ where
     }
 }
 
+// Ends the reader at `end`, so minimp3 doesn't see the trailing tags.
+struct Bounded<R> {
+    inner: R,
+    position: u64,
+    end: u64,
+}
+
+impl<R: Read> Read for Bounded<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let left = self.end.saturating_sub(self.position).min(buf.len() as u64);
+        let read = self.inner.read(&mut buf[..left as usize])?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl<R: Seek> Seek for Bounded<R> {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.position = self.inner.seek(pos)?;
+        Ok(self.position)
+    }
+}
+
+// What a SeekDecoder reads of the stream before minimp3 opens it.
+struct Metadata {
+    tag: Option<Tag>,
+    lame: Option<LameTag>,
+    trailer: Trailer,
+}
+
+impl Metadata {
+    // Reads the stream starting at `start`, and seeks back to it.
+    fn read<R: Read + Seek>(reader: &mut R, start: u64) -> io::Result<Metadata> {
+        let tag = Tag::read_from(reader)?;
+        if tag.is_none() {
+            reader.seek(io::SeekFrom::Start(start))?;
+        }
+        let lame = read_lame_tag(reader)?;
+        let trailer = Trailer::read(reader)?;
+        reader.seek(io::SeekFrom::Start(start))?;
+        Ok(Metadata { tag, lame, trailer })
+    }
+}
+
+// minimp3 parses the VBR header of a SeekDecoder itself, but doesn't keep the
+// LAME tag. Finds it in the first frame at the position of `reader`.
+fn read_lame_tag<R: Read>(reader: &mut R) -> io::Result<Option<LameTag>> {
+    // Enough for the largest layer III frame, and some junk before it.
+    let mut data = vec![0; 4096];
+    let len = id3::read_full(reader, &mut data)?;
+    let data = &data[..len];
+    let start = (0..len).find(|&i| FrameHeader::parse(&data[i..]).is_some());
+    Ok(start.and_then(|start| VbrHeader::parse(&data[start..])?.lame))
+}
+
 // Need to box this to avoid pointers being invalidated due to movement
-struct Mp3dec<R> {
-    reader: R,
+struct Mp3dec<R, S: Sample> {
+    reader: Bounded<R>,
     io: ffi::mp3dec_io_t,
-    ex: ffi::mp3dec_ex_t,
+    ex: S::Ex,
+}
+
+impl<R, S: Sample> Drop for Mp3dec<R, S> {
+    fn drop(&mut self) {
+        // Frees the seek index and the read buffer minimp3 allocated in
+        // `mp3dec_ex_open_cb`. The close zeroes `ex`, so closing twice is fine.
+        unsafe { S::ex_close(&mut self.ex) }
+    }
 }
 
 /// A sample level Seekable MP3 decoder which consumes a reader and produces samples.
 ///
 /// Unlike `Decoder` this requires `Seek` + `Read`. Also when possible, depending on the mp3 encoder this will trim of samples that were added as part of the encoding process.
-pub struct SeekDecoder<R> {
-    decoder: Box<Mp3dec<R>>,
+///
+/// The samples are `i16` unless the decoder was created with
+/// [`new_float`](SeekDecoder::new_float).
+///
+/// Positions and the duration count from the first sample of audio: when
+/// the stream has a LAME tag, minimp3 drops the silent Xing/Info frame, the
+/// samples the encoder and the decoder added at the start and the padding at
+/// the end, like [`Decoder::set_gapless`](Decoder::set_gapless) does. Position
+/// zero is then the first sample of the original audio, and
+/// [`duration`](SeekDecoder::duration) its length. Streams without a LAME tag
+/// keep these samples.
+pub struct SeekDecoder<R, S: Sample = i16> {
+    decoder: Box<Mp3dec<R, S>>,
+    // Index of the next sample, counting every channel.
+    position: u64,
+    seek_mode: SeekMode,
+    // The samples trimmed at the end, counting every channel.
+    padding: u64,
+    tag: Option<Tag>,
+    // The start and end in milliseconds of the chapters of the tag, in
+    // playing order.
+    chapters: Vec<(u64, u64)>,
+    trailer: Trailer,
+    replay_gain: ReplayGain,
+    gain: Option<GainOptions>,
+    // The last frame minimp3 decoded, whose samples it may still be handing
+    // out.
+    frame: Option<FrameInfo>,
 }
 
 // Explicitly impl [Send] for [SeekDecoder]. This isn't a great idea and should
 // probably be removed in the future. However we need raw pointers
-unsafe impl<R: Send> Send for SeekDecoder<R> {}
+unsafe impl<R: Send, S: Sample> Send for SeekDecoder<R, S> {}
 
 impl<R> SeekDecoder<R>
 where
     R: Read + Seek,
 {
-    /// Creates a new `SeekDecoder`, consuming the `reader`.
+    /// Creates a new `SeekDecoder`, consuming the `reader`. Seeks are
+    /// sample accurate, see [`SeekDecoderBuilder`](SeekDecoderBuilder) for
+    /// the other modes.
     pub fn new(reader: R) -> Result<SeekDecoder<R>, Error> {
+        SeekDecoderBuilder::new().build(reader)
+    }
+
+    /// Reopens a stream with the seek index saved by
+    /// [`seek_index`](SeekDecoder::seek_index), without reading the whole
+    /// stream. Fails with [`Error::StaleIndex`](Error::StaleIndex) if the
+    /// stream doesn't match the index.
+    pub fn new_with_index(reader: R, index: &SeekIndex) -> Result<SeekDecoder<R>, Error> {
+        Self::open_with_index(reader, index)
+    }
+}
+
+impl<R> SeekDecoder<R, f32>
+where
+    R: Read + Seek,
+{
+    /// Creates a new `SeekDecoder` producing `f32` samples, consuming the
+    /// `reader`.
+    pub fn new_float(reader: R) -> Result<SeekDecoder<R, f32>, Error> {
+        SeekDecoderBuilder::new().build_float(reader)
+    }
+
+    /// Like [`new_with_index`](SeekDecoder::new_with_index), producing `f32`
+    /// samples.
+    pub fn new_float_with_index(
+        reader: R,
+        index: &SeekIndex,
+    ) -> Result<SeekDecoder<R, f32>, Error> {
+        Self::open_with_index(reader, index)
+    }
+}
+
+impl<'a> SeekDecoder<Cursor<&'a [u8]>> {
+    /// Creates a new `SeekDecoder` decoding straight from `data`, without
+    /// copying it through a reader. The decoder borrows `data` for as long
+    /// as it lives.
+    pub fn from_slice(data: &'a [u8]) -> Result<SeekDecoder<Cursor<&'a [u8]>>, Error> {
+        Self::from_bytes(data)
+    }
+}
+
+impl<B> SeekDecoder<Cursor<B>>
+where
+    B: AsRef<[u8]>,
+{
+    /// Like [`from_slice`](SeekDecoder::from_slice), but takes ownership of
+    /// the buffer, like a `Vec<u8>` or an `Arc<[u8]>`.
+    /// [`into_inner`](SeekDecoder::into_inner) gives it back in a `Cursor`.
+    pub fn from_bytes(bytes: B) -> Result<SeekDecoder<Cursor<B>>, Error> {
+        SeekDecoderBuilder::new().build_bytes(bytes)
+    }
+}
+
+impl<B, S> SeekDecoder<Cursor<B>, S>
+where
+    B: AsRef<[u8]>,
+    S: Sample,
+{
+    fn open_bytes(
+        bytes: B,
+        builder: SeekDecoderBuilder,
+    ) -> Result<SeekDecoder<Cursor<B>, S>, Error> {
+        let mut reader = Cursor::new(bytes);
+        let metadata = Metadata::read(&mut reader, 0)?;
         let mut minidec = Box::new(Mp3dec {
-            reader,
+            reader: Bounded {
+                inner: reader,
+                position: 0,
+                end: metadata.trailer.start,
+            },
             io: unsafe { mem::zeroed() },
             ex: unsafe { mem::zeroed() },
         });
-        
-        // can only set the io fields here as the memory location of the 
+
+        // minimp3 keeps a pointer to the buffer, which stays put as the
+        // decoder owns or borrows it, and never gives it out mutably.
+        let data = minidec.reader.inner.get_ref().as_ref();
+        let len = data.len().min(metadata.trailer.start as usize);
+        let res = unsafe { S::ex_open_buf(&mut minidec.ex, data.as_ptr(), len, builder.flags()) };
+        from_mini_error(res)?;
+
+        Ok(Self::from_opened(minidec, builder, metadata))
+    }
+}
+
+/// How a [`SeekDecoder`](SeekDecoder) seeks, see
+/// [`SeekDecoderBuilder`](SeekDecoderBuilder).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SeekMode {
+    /// Seeks land on the requested sample, using an index of every frame of
+    /// the stream.
+    #[default]
+    Sample,
+    /// Seeks jump to a byte offset estimated from the bitrate, without an
+    /// index.
+    Byte,
+}
+
+/// Opens a [`SeekDecoder`](SeekDecoder) with a choice of how it seeks and
+/// whether it reads the whole stream up front.
+///
+/// - [`SeekMode::Sample`](SeekMode::Sample) with scanning, the default of
+///   [`SeekDecoder::new`](SeekDecoder::new): opening reads the whole stream,
+///   unless it starts with a Xing/Info or VBRI header, in which case the
+///   first seek does. Seeks are sample accurate.
+/// - `SeekMode::Sample` without scanning: opening only reads the start of
+///   the stream, and the first seek reads the whole stream to build the
+///   index. Seeks are sample accurate.
+/// - [`SeekMode::Byte`](SeekMode::Byte): seeks jump to the byte offset
+///   where the sample would be if the bitrate were constant, and decoding
+///   resumes at the next frame. This is accurate to a frame or two for
+///   constant bitrate streams, and a guess for variable bitrate ones. The
+///   positions reported after a seek count from the requested sample, and
+///   the first frame after it may be dropped for lacking the bit reservoir
+///   of the frames before it. The padding at the end of the stream isn't
+///   trimmed after a seek. Without scanning nothing ever reads the whole
+///   stream.
+///
+/// Without scanning, [`total_samples`](SeekDecoder::total_samples) and
+/// [`duration`](SeekDecoder::duration) are only known from a Xing/Info or
+/// VBRI header, and zero otherwise.
+///
+/// ```no_run
+/// use minimp3_fixed::{SeekDecoderBuilder, SeekMode};
+///
+/// let file = std::fs::File::open("file.mp3").unwrap();
+/// let mut decoder = SeekDecoderBuilder::new()
+///     .seek_mode(SeekMode::Byte)
+///     .scan(false)
+///     .build(file)
+///     .unwrap();
+/// decoder.seek_to(std::time::Duration::from_secs(60)).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SeekDecoderBuilder {
+    seek_mode: SeekMode,
+    scan: bool,
+}
+
+impl Default for SeekDecoderBuilder {
+    fn default() -> Self {
+        SeekDecoderBuilder {
+            seek_mode: SeekMode::Sample,
+            scan: true,
+        }
+    }
+}
+
+impl SeekDecoderBuilder {
+    /// A builder for sample accurate seeking, scanning the stream when
+    /// opening it.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How the decoder seeks. [`SeekMode::Sample`](SeekMode::Sample) by
+    /// default.
+    pub fn seek_mode(mut self, seek_mode: SeekMode) -> Self {
+        self.seek_mode = seek_mode;
+        self
+    }
+
+    /// Whether opening the stream reads all of it, to count its samples and
+    /// build the seek index. On by default.
+    pub fn scan(mut self, scan: bool) -> Self {
+        self.scan = scan;
+        self
+    }
+
+    /// Opens a decoder producing `i16` samples, consuming the `reader`.
+    pub fn build<R: Read + Seek>(self, reader: R) -> Result<SeekDecoder<R>, Error> {
+        SeekDecoder::open(reader, self)
+    }
+
+    /// Opens a decoder producing `f32` samples, consuming the `reader`.
+    pub fn build_float<R: Read + Seek>(self, reader: R) -> Result<SeekDecoder<R, f32>, Error> {
+        SeekDecoder::open(reader, self)
+    }
+
+    /// Opens a decoder producing `i16` samples, decoding straight from
+    /// `bytes`, see [`SeekDecoder::from_bytes`](SeekDecoder::from_bytes).
+    pub fn build_bytes<B: AsRef<[u8]>>(self, bytes: B) -> Result<SeekDecoder<Cursor<B>>, Error> {
+        SeekDecoder::open_bytes(bytes, self)
+    }
+
+    /// Opens a decoder producing `f32` samples, decoding straight from
+    /// `bytes`.
+    pub fn build_bytes_float<B: AsRef<[u8]>>(
+        self,
+        bytes: B,
+    ) -> Result<SeekDecoder<Cursor<B>, f32>, Error> {
+        SeekDecoder::open_bytes(bytes, self)
+    }
+
+    fn flags(&self) -> c_int {
+        let mode = match self.seek_mode {
+            SeekMode::Sample => ffi::MP3D_SEEK_TO_SAMPLE,
+            SeekMode::Byte => ffi::MP3D_SEEK_TO_BYTE,
+        };
+        let scan = if self.scan { 0 } else { ffi::MP3D_DO_NOT_SCAN };
+        (mode | scan) as c_int
+    }
+}
+
+impl<R, S> SeekDecoder<R, S>
+where
+    R: Read + Seek,
+    S: Sample,
+{
+    fn open(mut reader: R, builder: SeekDecoderBuilder) -> Result<SeekDecoder<R, S>, Error> {
+        let start = reader.stream_position()?;
+        let metadata = Metadata::read(&mut reader, start)?;
+        let trailer = &metadata.trailer;
+
+        let mut minidec = Box::new(Mp3dec {
+            reader: Bounded {
+                inner: reader,
+                position: start,
+                end: trailer.start,
+            },
+            io: unsafe { mem::zeroed() },
+            ex: unsafe { mem::zeroed() },
+        });
+
+        // can only set the io fields here as the memory location of the
         // reader must stay constant (which the Box::new takes care of)
-        minidec.io.read = Some(read_callback::<R>); 
-        minidec.io.seek = Some(seek_callback::<R>);
+        minidec.io.read = Some(read_callback::<Bounded<R>>);
+        minidec.io.seek = Some(seek_callback::<Bounded<R>>);
         // data needed by the callbacks set above, passed as C void pointer
-        minidec.io.read_data = &mut minidec.reader as * mut _ as *mut c_void;
-        minidec.io.seek_data = &mut minidec.reader as * mut _ as *mut c_void;
-        
+        minidec.io.read_data = &mut minidec.reader as *mut _ as *mut c_void;
+        minidec.io.seek_data = &mut minidec.reader as *mut _ as *mut c_void;
+
         // open the reader
-        let res = unsafe {
-            ffi::mp3dec_ex_open_cb(
-                &mut minidec.ex,
-                &mut minidec.io,
-                ffi::MP3D_SEEK_TO_SAMPLE as i32,
-            )
-        };
+        let res = unsafe { S::ex_open_cb(&mut minidec.ex, &mut minidec.io, builder.flags()) };
         from_mini_error(res)?;
-        
-        Ok(SeekDecoder {
-            decoder: minidec,
+
+        Ok(Self::from_opened(minidec, builder, metadata))
+    }
+
+    fn from_opened(
+        decoder: Box<Mp3dec<R, S>>,
+        builder: SeekDecoderBuilder,
+        metadata: Metadata,
+    ) -> SeekDecoder<R, S> {
+        let Metadata { tag, lame, trailer } = metadata;
+        let replay_gain = ReplayGain::find(tag.as_ref(), Some(&trailer), lame.as_ref());
+        let channels = S::ex_info(&decoder.ex).channels.max(0) as u64;
+        let padding = lame.map_or(0, |lame| lame.trimmed_samples().1 * channels);
+        let chapters = tag.as_ref().map_or_else(Vec::new, Tag::chapters);
+        let chapters = chapters
+            .iter()
+            .map(|chapter| (chapter.start_ms as u64, chapter.end_ms as u64))
+            .collect();
+
+        SeekDecoder {
+            decoder,
+            position: 0,
+            seek_mode: builder.seek_mode,
+            padding,
+            tag,
+            chapters,
+            trailer,
+            replay_gain,
+            gain: None,
+            frame: None,
+        }
+    }
+
+    fn open_with_index(mut reader: R, index: &SeekIndex) -> Result<SeekDecoder<R, S>, Error> {
+        let start = reader.stream_position()?;
+        let end = reader.seek(io::SeekFrom::End(0))?;
+        reader.seek(io::SeekFrom::Start(start))?;
+        if end != index.size {
+            return Err(Error::StaleIndex);
+        }
+        if let Some(expected) = index.hash {
+            reader.seek(io::SeekFrom::Start(0))?;
+            let hash = index::hash(&mut reader)?;
+            reader.seek(io::SeekFrom::Start(start))?;
+            if hash != expected {
+                return Err(Error::StaleIndex);
+            }
+        }
+
+        // Reads only the first frame, which restores the length, delay and
+        // offset of the audio given by a VBR header.
+        let builder = SeekDecoderBuilder::new().scan(false);
+        let mut decoder = Self::open(reader, builder)?;
+        let ex = &mut decoder.decoder.ex;
+        if S::ex_start_offset(ex) != index.start_offset {
+            return Err(Error::StaleIndex);
+        }
+        let state = S::ex_index_state(ex);
+        if state.start_delay.max(0) as u64 != index.delay || decoder.padding != index.padding {
+            return Err(Error::StaleIndex);
+        }
+
+        // minimp3 frees the index with `free` when closing.
+        let len = index.frames.len();
+        let frames = unsafe {
+            libc::malloc(len.max(1) * mem::size_of::<ffi::mp3dec_frame_t>())
+                as *mut ffi::mp3dec_frame_t
+        };
+        if frames.is_null() {
+            return Err(Error::MiniMemory);
+        }
+        for (i, frame) in index.frames.iter().enumerate() {
+            let frame = ffi::mp3dec_frame_t {
+                sample: frame.sample,
+                offset: frame.offset,
+            };
+            unsafe { frames.add(i).write(frame) };
+        }
+        unsafe { libc::free(state.index.frames as *mut c_void) };
+        state.index.frames = frames;
+        state.index.num_frames = len as _;
+        state.index.capacity = len.max(1) as _;
+        *state.indexes_built = 1;
+        *state.samples = index.samples;
+        *state.detected_samples = index.detected_samples;
+        Ok(decoder)
+    }
+
+    /// The seek index of the stream, to save and reopen the stream with
+    /// [`new_with_index`](SeekDecoder::new_with_index). Builds the index
+    /// first if opening the stream didn't, which reads all of it.
+    ///
+    /// With [`IndexCheck::Hash`](IndexCheck::Hash) the whole stream is read
+    /// to hash it. Fails with [`Error::MiniParam`](Error::MiniParam) in
+    /// [`SeekMode::Byte`](SeekMode::Byte), which doesn't use an index.
+    pub fn seek_index(&mut self, check: IndexCheck) -> Result<SeekIndex, Error> {
+        if self.seek_mode == SeekMode::Byte {
+            return Err(Error::MiniParam);
+        }
+        if *S::ex_index_state(&mut self.decoder.ex).indexes_built == 0 {
+            // Any seek but to the first sample builds the index.
+            let res = unsafe { S::ex_seek(&mut self.decoder.ex, 1) };
+            from_mini_error(res)?;
+        }
+
+        let reader = &mut self.decoder.reader.inner;
+        let size = reader.seek(io::SeekFrom::End(0))?;
+        let hash = match check {
+            IndexCheck::Size => None,
+            IndexCheck::Hash => {
+                reader.seek(io::SeekFrom::Start(0))?;
+                Some(index::hash(&mut *reader))
+            }
+        };
+        // Returns to the position before, as minimp3 expects the reader
+        // where it left it.
+        self.seek_samples(self.position)?;
+        let hash = hash.transpose()?;
+
+        let start_offset = S::ex_start_offset(&self.decoder.ex);
+        let state = S::ex_index_state(&mut self.decoder.ex);
+        let frames = if state.index.frames.is_null() {
+            &[][..]
+        } else {
+            unsafe {
+                std::slice::from_raw_parts(state.index.frames, state.index.num_frames as usize)
+            }
+        };
+        Ok(SeekIndex {
+            size,
+            hash,
+            start_offset,
+            delay: state.start_delay.max(0) as u64,
+            padding: self.padding,
+            samples: *state.samples,
+            detected_samples: *state.detected_samples,
+            frames: frames
+                .iter()
+                .map(|frame| IndexFrame {
+                    sample: frame.sample,
+                    offset: frame.offset,
+                })
+                .collect(),
         })
     }
 
-    pub fn decode_frame(&mut self) -> Result<Frame, Error> {
+    /// Decodes the next frame. Returns [`Error::Eof`](Error::Eof) once the
+    /// end of the stream is reached, or the error minimp3 reported if it
+    /// stopped early.
+    pub fn decode_frame(&mut self) -> Result<Frame<S>, Error> {
+        self.next_frame_ref().map(|frame| frame.to_frame())
+    }
+
+    /// Like [`decode_frame`](SeekDecoder::decode_frame), but borrows the audio
+    /// from minimp3's internal buffer instead of copying it.
+    pub fn next_frame_ref(&mut self) -> Result<FrameRef<'_, S>, Error> {
+        self.read_frame(MAX_SAMPLES_PER_FRAME)
+    }
+
+    /// Like [`decode_frame`](SeekDecoder::decode_frame), but copies the audio
+    /// into `buf`. The decoded samples are `buf[..info.samples]`.
+    ///
+    /// If `buf` can't hold the whole frame the remaining samples are returned
+    /// by the next call. Returns
+    /// [`Error::InsufficientData`](Error::InsufficientData) if `buf` is empty.
+    pub fn next_frame_into(&mut self, buf: &mut [S]) -> Result<FrameInfo, Error> {
+        if buf.is_empty() {
+            return Err(Error::InsufficientData);
+        }
+        let frame = self.read_frame(buf.len())?;
+        buf[..frame.data.len()].copy_from_slice(frame.data);
+        Ok(frame.info)
+    }
+
+    fn read_frame(&mut self, max_samples: usize) -> Result<FrameRef<'_, S>, Error> {
         let mut frame_info = unsafe { mem::zeroed() };
         let mut buffer = std::ptr::null_mut();
+        // `buffer` is pointed at minimp3's internal buffer, which stays valid
+        // until the next call into the ex decoder.
         let samples: u64 = unsafe {
-            ffi::mp3dec_ex_read_frame(
+            S::ex_read_frame(
                 &mut self.decoder.ex,
-                &mut buffer, // seems to allocate its own memory.....
+                &mut buffer,
                 &mut frame_info,
-                MAX_SAMPLES_PER_FRAME as u64,
+                max_samples as u64,
             )
         };
 
+        // minimp3 skips over non-audio data internally, so no samples means
+        // either an error or the end of the stream.
+        if samples == 0 {
+            from_mini_error(S::ex_last_error(&self.decoder.ex))?;
+            return Err(Error::Eof);
+        }
+
+        // minimp3 only fills in `frame_info` when it decodes a frame, not
+        // when it hands out the rest of one `max_samples` cut short.
+        if frame_info.frame_bytes > 0 {
+            self.frame = Some(self.decoded_frame(&frame_info));
+        }
         let len = samples as usize;
-        let buffer = unsafe { std::slice::from_raw_parts(buffer, len)};
-        let buffer = buffer.to_owned();
+        let mut info = self
+            .frame
+            .unwrap_or_else(|| FrameInfo::from_ffi(&frame_info, len));
+        info.samples = len;
+        info.sample_position = self.position;
+        self.position += samples;
+        // minimp3 is done with the returned samples, so they can be changed
+        // in place.
+        let data = unsafe { std::slice::from_raw_parts_mut(buffer, len) };
+        self.apply_replay_gain(data);
+        Ok(FrameRef { data, info })
+    }
 
-        let frame = Frame {
-            data: buffer,
-            sample_rate: frame_info.hz,
-            channels: frame_info.channels as usize,
-            layer: frame_info.layer as usize,
-            bitrate: frame_info.bitrate_kbps,
-        };
+    // Describes the frame minimp3 just decoded, finding it in the stream.
+    fn decoded_frame(&self, frame_info: &ffi::mp3dec_frame_info_t) -> FrameInfo {
+        let mut info = FrameInfo::from_ffi(frame_info, 0);
+        // minimp3 stops right after the frame, both in the stream and in its
+        // read buffer.
+        let (end, data) = S::ex_frame_end(&self.decoder.ex);
+        let frame =
+            unsafe { std::slice::from_raw_parts(data.sub(info.frame_bytes), info.frame_bytes) };
+        info.offset = Some(end - info.frame_bytes as u64);
+        info.header = FrameHeader::parse(frame);
+        info
+    }
 
-        if samples == 0 {
-            if frame_info.frame_bytes > 0 {
-                Err(Error::SkippedData)
-            } else {
-                Err(Error::InsufficientData)
-            }
-        } else {
-            Ok(frame)
+    fn apply_replay_gain(&self, samples: &mut [S]) {
+        if let Some(options) = &self.gain {
+            let factor = options.factor(&self.replay_gain);
+            gain::apply(samples, factor, options.prevent_clipping);
         }
     }
 
     /// This mp3s sample rate in hertz, when using read_samples or read_sample_slice this can
     /// for every sample returned
     pub fn current_sample_rate(&self) -> i32 {
-        self.decoder.ex.info.hz
+        S::ex_info(&self.decoder.ex).hz
     }
     /// The number of channels in this mp3, when using read_samples or read_sample_slice this can
     /// for every sample returned
     pub fn _current_channels(&self) -> usize {
-        self.decoder.ex.info.channels as usize
+        S::ex_info(&self.decoder.ex).channels as usize
+    }
+
+    /// The number of samples of the stream, counting every channel. Taken
+    /// from the Xing/Info or VBRI header if there is one, otherwise counted
+    /// while opening the stream.
+    pub fn total_samples(&self) -> u64 {
+        S::ex_samples(&self.decoder.ex)
+    }
+
+    /// The ID3v2 tag of the stream, from its start or, failing that, its
+    /// end.
+    pub fn tag(&self) -> Option<&Tag> {
+        self.tag.as_ref().or(self.trailer.id3v2.as_ref())
+    }
+
+    /// The tags at the end of the stream, where decoding stops.
+    pub fn trailer(&self) -> &Trailer {
+        &self.trailer
+    }
+
+    /// The chapters of the stream from its ID3v2 tag, in playing order.
+    pub fn chapters(&self) -> Vec<&Chapter> {
+        self.tag().map_or_else(Vec::new, Tag::chapters)
+    }
+
+    /// Applies ReplayGain to the decoded audio, or stops applying it with
+    /// `None`. Off by default.
+    pub fn set_replay_gain(&mut self, options: Option<GainOptions>) {
+        self.gain = options;
+    }
+
+    /// The ReplayGain values of the stream, from the ID3v2 tag at its start,
+    /// the LAME tag, and the tags at its end.
+    pub fn replay_gain(&self) -> ReplayGain {
+        self.replay_gain
+    }
+
+    /// Seeks to the start of the chapter at `index` in
+    /// [`chapters`](SeekDecoder::chapters).
+    pub fn seek_to_chapter(&mut self, index: usize) -> Result<(), Error> {
+        let &(start_ms, _) = self.chapters.get(index).ok_or(Error::NoSuchChapter)?;
+        self.seek_to(Duration::from_millis(start_ms))
+    }
+
+    /// The index in [`chapters`](SeekDecoder::chapters) of the chapter the
+    /// next sample is in.
+    pub fn current_chapter(&self) -> Option<usize> {
+        let info = S::ex_info(&self.decoder.ex);
+        if info.hz <= 0 || info.channels <= 0 {
+            return None;
+        }
+        // Rounds up, so seeking to a chapter lands in it.
+        let ms = (self.position / info.channels as u64 * 1000).div_ceil(info.hz as u64);
+        self.chapters
+            .iter()
+            .position(|&(start, end)| start <= ms && ms < end)
+    }
+
+    /// A cursor finding the line of `lyrics` playing at the
+    /// [`sample_position`](Frame::sample_position) of the decoded frames.
+    pub fn lyrics_cursor(&self, lyrics: SyncedLyrics) -> LyricsCursor {
+        let info = S::ex_info(&self.decoder.ex);
+        let samples_per_frame = samples_per_frame(info.layer as usize, info.hz);
+        LyricsCursor::new(
+            lyrics,
+            info.hz.max(0) as u32,
+            info.channels.max(0) as usize,
+            samples_per_frame,
+        )
+    }
+
+    /// The duration of the stream, without the samples trimmed as given by
+    /// its LAME tag.
+    pub fn duration(&self) -> Duration {
+        let info = S::ex_info(&self.decoder.ex);
+        duration(self.total_samples(), info.channels as usize, info.hz)
+    }
+
+    /// The time of the next sample since the start of the stream. Rounded
+    /// up to a nanosecond, so [`seek_to`](SeekDecoder::seek_to) returns to
+    /// the same sample.
+    pub fn position(&self) -> Duration {
+        let hz = S::ex_info(&self.decoder.ex).hz;
+        if hz <= 0 {
+            return Duration::ZERO;
+        }
+        let nanos = (self.position_samples() as u128 * 1_000_000_000).div_ceil(hz as u128);
+        Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+    }
+
+    /// The index of the next sample per channel, unlike
+    /// [`seek_samples`](SeekDecoder::seek_samples) which counts every
+    /// channel.
+    pub fn position_samples(&self) -> u64 {
+        let channels = S::ex_info(&self.decoder.ex).channels.max(1);
+        self.position / channels as u64
     }
 
     /// Returns the number of samples that were set
     /// Will be zero at end of stream
-    pub fn read_samples(&mut self, buf: &mut [i16]) -> Result<usize, Error> {
+    pub fn read_samples(&mut self, buf: &mut [S]) -> Result<usize, Error> {
         let len = unsafe {
-            ffi::mp3dec_ex_read(&mut self.decoder.ex, buf.as_mut_ptr(), buf.len() as u64) as usize
+            S::ex_read(&mut self.decoder.ex, buf.as_mut_ptr(), buf.len() as u64) as usize
         };
+        self.position += len as u64;
+        let decoded = len.min(buf.len());
+        self.apply_replay_gain(&mut buf[..decoded]);
 
         if len == buf.len() {
             Ok(len)
         } else if len < buf.len() {
             // Check for error
-            from_mini_error(self.decoder.ex.last_error)?;
+            from_mini_error(S::ex_last_error(&self.decoder.ex))?;
             // Must be end of stream
             Ok(len)
         } else {
@@ -366,8 +1266,8 @@ where
     /// Returns the slice of newly assigned samples otherwise
     pub fn read_sample_slice<'a>(
         &mut self,
-        buf: &'a mut [i16],
-    ) -> Result<Option<&'a mut [i16]>, Error> {
+        buf: &'a mut [S],
+    ) -> Result<Option<&'a mut [S]>, Error> {
         let len = self.read_samples(buf)?;
         Ok(if len == 0 {
             None
@@ -376,14 +1276,68 @@ where
         })
     }
 
-    /// Seek to the given sample index
+    /// Seek to the given sample index, counting every channel. The index
+    /// should be a multiple of the number of channels.
+    ///
+    /// In [`SeekMode::Byte`](SeekMode::Byte) this lands near the sample, see
+    /// [`SeekDecoderBuilder`](SeekDecoderBuilder).
     pub fn seek_samples(&mut self, sample: u64) -> Result<(), Error> {
-        let res = unsafe { ffi::mp3dec_ex_seek(&mut self.decoder.ex, sample) };
-        from_mini_error(res)
+        let position = match self.seek_mode {
+            SeekMode::Sample => sample,
+            SeekMode::Byte => self.byte_offset(sample),
+        };
+        let res = unsafe { S::ex_seek(&mut self.decoder.ex, position) };
+        from_mini_error(res)?;
+        self.position = sample;
+        Ok(())
+    }
+
+    /// How the decoder seeks.
+    pub fn seek_mode(&self) -> SeekMode {
+        self.seek_mode
+    }
+
+    // Estimates the offset of `sample` in the stream assuming a constant
+    // bitrate: proportionally between the first frame and the trailer if
+    // the length is known, from the bitrate of the current frame otherwise.
+    fn byte_offset(&self, sample: u64) -> u64 {
+        let ex = &self.decoder.ex;
+        let start = S::ex_start_offset(ex);
+        let total = self.total_samples();
+        if total > 0 {
+            let bytes = self.trailer.start.saturating_sub(start);
+            let offset = bytes as u128 * sample as u128 / total as u128;
+            return start.saturating_add(u64::try_from(offset).unwrap_or(u64::MAX));
+        }
+
+        let info = S::ex_info(ex);
+        if info.hz <= 0 || info.channels <= 0 {
+            return start;
+        }
+        // kbit/s to bytes per second.
+        let bytes_per_second = info.bitrate_kbps.max(0) as u128 * 125;
+        let frames = sample as u128 / info.channels as u128;
+        let offset = frames * bytes_per_second / info.hz as u128;
+        start.saturating_add(u64::try_from(offset).unwrap_or(u64::MAX))
+    }
+
+    /// Seeks to `time` since the start of the stream, rounded down to a
+    /// sample. Seeking past the end leaves nothing to decode.
+    pub fn seek_to(&mut self, time: Duration) -> Result<(), Error> {
+        let info = S::ex_info(&self.decoder.ex);
+        let frame = time.as_nanos() * info.hz.max(0) as u128 / 1_000_000_000;
+        let frame = u64::try_from(frame).unwrap_or(u64::MAX);
+        self.seek_samples(frame.saturating_mul(info.channels.max(0) as u64))
     }
 
     /// Destroy the decoder and return the inner reader
     pub fn into_inner(self) -> R {
-        self.decoder.reader
+        let mut decoder = self.decoder;
+        unsafe { S::ex_close(&mut decoder.ex) };
+        // `Mp3dec` implements `Drop`, so the reader can't be moved out of it
+        // directly. The ex decoder is already closed and the remaining fields
+        // are plain C data, so only the reader needs to be read out.
+        let decoder = mem::ManuallyDrop::new(*decoder);
+        unsafe { std::ptr::read(&decoder.reader.inner) }
     }
 }
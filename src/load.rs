@@ -0,0 +1,179 @@
+use crate::{
+    error::from_mini_error, ffi, read_callback, seek_callback, trailer::Trailer, Bounded, Error,
+    Sample,
+};
+
+use std::{
+    any::Any,
+    io::{self, Read, Seek},
+    mem,
+    ops::ControlFlow,
+    os::raw::{c_int, c_void},
+    panic::{self, AssertUnwindSafe},
+};
+
+/// The whole audio of a stream, decoded by [`decode_all`](decode_all).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DecodedAudio<S = i16> {
+    /// The decoded audio. Channels are interleaved.
+    pub samples: Vec<S>,
+    /// The number of channels.
+    pub channels: usize,
+    /// The sample rate in hertz.
+    pub sample_rate: i32,
+    /// MPEG layer used by the stream.
+    pub layer: usize,
+    /// The average bitrate of the stream in kb/s.
+    pub avg_bitrate_kbps: i32,
+}
+
+/// How far [`DecodedAudio::decode`](DecodedAudio::decode) is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DecodeProgress {
+    /// The bytes read so far.
+    pub position: u64,
+    /// The length of the stream in bytes, from where the reader was handed
+    /// over and without the tags at its end.
+    pub size: u64,
+}
+
+/// Decodes the whole stream of `reader` at once, from its current position,
+/// like a [`SeekDecoder`](crate::SeekDecoder) would: without the ID3v2 tag, the
+/// Xing/Info frame, the samples trimmed as given by the LAME tag, and the
+/// tags at the end of the stream.
+///
+/// ```no_run
+/// let file = std::fs::File::open("file.mp3").unwrap();
+/// let audio = minimp3_fixed::decode_all(file).unwrap();
+/// println!("{} samples at {} Hz", audio.samples.len(), audio.sample_rate);
+/// ```
+pub fn decode_all<R: Read + Seek>(reader: R) -> Result<DecodedAudio, Error> {
+    DecodedAudio::decode(reader, None)
+}
+
+// The user data of the progress callback.
+struct Progress<'a, R> {
+    callback: &'a mut dyn FnMut(DecodeProgress) -> ControlFlow<()>,
+    reader: *const Bounded<R>,
+    // Where the stream starts in the reader.
+    start: u64,
+    cancelled: bool,
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+unsafe extern "C" fn progress_callback<R>(
+    user_data: *mut c_void,
+    _file_size: ffi::size_t,
+    _offset: u64,
+    _info: *mut ffi::mp3dec_frame_info_t,
+) -> c_int {
+    let progress = &mut *(user_data as *mut Progress<'_, R>);
+    // minimp3 reports the offset in its read buffer, so the position is
+    // taken from the reader instead.
+    let reader = &*progress.reader;
+    let report = DecodeProgress {
+        position: reader.position.saturating_sub(progress.start),
+        size: reader.end.saturating_sub(progress.start),
+    };
+    // Unwinding into C is undefined behaviour, so a panic stops decoding
+    // and is resumed once minimp3 returned.
+    match panic::catch_unwind(AssertUnwindSafe(|| (progress.callback)(report))) {
+        Ok(ControlFlow::Continue(())) => 0,
+        Ok(ControlFlow::Break(())) => {
+            progress.cancelled = true;
+            1
+        }
+        Err(payload) => {
+            progress.panic = Some(payload);
+            1
+        }
+    }
+}
+
+impl<S: Sample> DecodedAudio<S> {
+    /// Like [`decode_all`](decode_all), for either sample type, reporting
+    /// the progress to `progress` after every frame. Decoding stops with
+    /// [`Error::Cancelled`](Error::Cancelled) if it returns
+    /// `ControlFlow::Break`.
+    pub fn decode<R: Read + Seek>(
+        mut reader: R,
+        progress: Option<&mut dyn FnMut(DecodeProgress) -> ControlFlow<()>>,
+    ) -> Result<DecodedAudio<S>, Error> {
+        let start = reader.stream_position()?;
+        let trailer = Trailer::read(&mut reader)?;
+        reader.seek(io::SeekFrom::Start(start))?;
+        let mut reader = Box::new(Bounded {
+            inner: reader,
+            position: start,
+            end: trailer.start.max(start),
+        });
+
+        let reader_ptr = &mut *reader as *mut Bounded<R>;
+        let mut io = ffi::mp3dec_io_t {
+            read: Some(read_callback::<Bounded<R>>),
+            read_data: reader_ptr as *mut c_void,
+            seek: Some(seek_callback::<Bounded<R>>),
+            seek_data: reader_ptr as *mut c_void,
+        };
+        let mut progress = progress.map(|callback| Progress {
+            callback,
+            reader: reader_ptr,
+            start,
+            cancelled: false,
+            panic: None,
+        });
+        let (progress_cb, user_data): (ffi::MP3D_PROGRESS_CB, _) = match &mut progress {
+            Some(progress) => (
+                Some(progress_callback::<R>),
+                progress as *mut Progress<'_, R> as *mut c_void,
+            ),
+            None => (None, std::ptr::null_mut()),
+        };
+
+        let mut decoder: Box<ffi::mp3dec_t> = Box::new(unsafe { mem::zeroed() });
+        unsafe { ffi::mp3dec_init(&mut *decoder) };
+        let mut buf = vec![0u8; ffi::MINIMP3_BUF_SIZE as usize];
+        let mut info: S::FileInfo = unsafe { mem::zeroed() };
+        let res = unsafe {
+            S::load_cb(
+                &mut *decoder,
+                &mut io,
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut info,
+                progress_cb,
+                user_data,
+            )
+        };
+
+        // minimp3 allocates the samples with `malloc`, and may leave them
+        // allocated when it fails.
+        let info = S::file_info(&info);
+        let samples = if info.buffer.is_null() {
+            Vec::new()
+        } else {
+            let samples = unsafe { std::slice::from_raw_parts(info.buffer, info.samples) };
+            let samples = samples.to_vec();
+            unsafe { libc::free(info.buffer as *mut c_void) };
+            samples
+        };
+
+        if let Some(progress) = progress {
+            if let Some(payload) = progress.panic {
+                panic::resume_unwind(payload);
+            }
+            if progress.cancelled {
+                return Err(Error::Cancelled);
+            }
+        }
+        from_mini_error(res)?;
+
+        Ok(DecodedAudio {
+            samples,
+            channels: info.channels.max(0) as usize,
+            sample_rate: info.hz,
+            layer: info.layer.max(0) as usize,
+            avg_bitrate_kbps: info.avg_bitrate_kbps,
+        })
+    }
+}
@@ -0,0 +1,265 @@
+//! Finding the pictures embedded in the tags of a stream: the `APIC` and
+//! `PIC` frames of ID3v2 tags, and the `Cover Art` items of APE tags.
+//!
+//! [`find_pictures`](find_pictures) only reads the fields describing each
+//! picture of the ID3v2 tag at the start of the stream, and remembers where
+//! its image data is, so large artwork can be streamed with
+//! [`EmbeddedPicture::open`](EmbeddedPicture::open).
+//!
+//! ```no_run
+//! use minimp3_fixed::picture::find_pictures;
+//! use std::io::Read;
+//!
+//! let mut file = std::fs::File::open("file.mp3").unwrap();
+//! for picture in find_pictures(&mut file).unwrap() {
+//!     let mut image = picture.open(&mut file).unwrap();
+//!     let mut out = std::fs::File::create("cover.jpg").unwrap();
+//!     std::io::copy(&mut image, &mut out).unwrap();
+//! }
+//! ```
+
+use crate::{
+    ape::ApeTag,
+    id3::{self, FrameFormat, Picture, Tag},
+    trailer::Trailer,
+};
+use std::io::{self, Read, Seek, SeekFrom};
+
+// The fields before the image data are short, but the description has no
+// limit. Reading starts with this many bytes and grows until it fits.
+const PICTURE_HEADER_SIZE: usize = 1024;
+
+/// A picture found by [`find_pictures`](find_pictures), without its image
+/// data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddedPicture {
+    pub mime_type: String,
+    /// What the picture shows, 3 for the front cover. See
+    /// [`PICTURE_TYPES`](crate::id3::PICTURE_TYPES).
+    pub picture_type: u8,
+    pub description: String,
+    /// The size of the image data in bytes.
+    pub size: u64,
+    data: Data,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Data {
+    // Stored as is at this stream offset.
+    Stream(u64),
+    // Stored compressed or unsynchronised, or in a tag which was read whole.
+    Memory(Vec<u8>),
+}
+
+impl From<Picture> for EmbeddedPicture {
+    fn from(picture: Picture) -> Self {
+        EmbeddedPicture {
+            mime_type: picture.mime_type,
+            picture_type: picture.picture_type,
+            description: picture.description,
+            size: picture.data.len() as u64,
+            data: Data::Memory(picture.data),
+        }
+    }
+}
+
+impl EmbeddedPicture {
+    /// A reader of the image data. `reader` must be the reader the picture
+    /// was found in.
+    pub fn open<'a, R: Read + Seek>(
+        &'a self,
+        reader: &'a mut R,
+    ) -> io::Result<PictureReader<'a, R>> {
+        let inner = match &self.data {
+            Data::Stream(offset) => {
+                reader.seek(SeekFrom::Start(*offset))?;
+                Inner::Stream(Read::take(reader, self.size))
+            }
+            Data::Memory(data) => Inner::Memory(data),
+        };
+        Ok(PictureReader { inner })
+    }
+
+    /// Reads the whole image data.
+    pub fn read<R: Read + Seek>(&self, reader: &mut R) -> io::Result<Vec<u8>> {
+        let mut data = Vec::new();
+        self.open(reader)?.read_to_end(&mut data)?;
+        Ok(data)
+    }
+}
+
+/// Reads the image data of an [`EmbeddedPicture`](EmbeddedPicture).
+pub struct PictureReader<'a, R> {
+    inner: Inner<'a, R>,
+}
+
+enum Inner<'a, R> {
+    Stream(io::Take<&'a mut R>),
+    Memory(&'a [u8]),
+}
+
+impl<R: Read> Read for PictureReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match &mut self.inner {
+            Inner::Stream(reader) => reader.read(buf),
+            Inner::Memory(data) => data.read(buf),
+        }
+    }
+}
+
+/// Finds the pictures in the ID3v2 tag at the current position of `reader`,
+/// and in the tags at its end. The position of `reader` is left anywhere.
+///
+/// The image data of the pictures in the tags at the end, and of ID3v2
+/// frames which are compressed or unsynchronised, is read into memory.
+pub fn find_pictures<R: Read + Seek>(reader: &mut R) -> io::Result<Vec<EmbeddedPicture>> {
+    let mut pictures = Vec::new();
+    scan_id3v2(reader, &mut pictures)?;
+
+    let trailer = Trailer::read(reader)?;
+    let id3v2 = trailer.id3v2.iter().flat_map(Tag::pictures).cloned();
+    let ape = trailer.ape.iter().flat_map(ApeTag::pictures);
+    pictures.extend(id3v2.chain(ape).map(EmbeddedPicture::from));
+    Ok(pictures)
+}
+
+/// Like [`find_pictures`](find_pictures), but reads the image data of every
+/// picture.
+pub fn read_pictures<R: Read + Seek>(reader: &mut R) -> io::Result<Vec<Picture>> {
+    find_pictures(reader)?
+        .into_iter()
+        .map(|picture| {
+            let data = picture.read(reader)?;
+            Ok(Picture {
+                mime_type: picture.mime_type,
+                picture_type: picture.picture_type,
+                description: picture.description,
+                data,
+            })
+        })
+        .collect()
+}
+
+fn scan_id3v2<R: Read + Seek>(
+    reader: &mut R,
+    pictures: &mut Vec<EmbeddedPicture>,
+) -> io::Result<()> {
+    let start = reader.stream_position()?;
+    let mut header = [0; id3::HEADER_SIZE];
+    if id3::read_full(reader, &mut header)? < id3::HEADER_SIZE {
+        return Ok(());
+    }
+    let size = match id3::tag_size(&header) {
+        Some(size) => size,
+        None => return Ok(()),
+    };
+    let (version, flags) = (header[3], header[5]);
+    let unsynchronised = flags & id3::FLAG_UNSYNCHRONISATION != 0;
+
+    // Unsynchronising the whole tag also changes the frame headers, and the
+    // compression of ID3v2.2 tags was never specified. Such tags are read
+    // whole.
+    let extended = flags & id3::FLAG_EXTENDED_HEADER != 0;
+    if (version < 4 && unsynchronised) || (version == 2 && extended) {
+        reader.seek(SeekFrom::Start(start))?;
+        if let Some(tag) = Tag::read_from(reader)? {
+            pictures.extend(tag.pictures().cloned().map(EmbeddedPicture::from));
+        }
+        return Ok(());
+    }
+
+    let footer = if flags & id3::FLAG_FOOTER != 0 {
+        id3::HEADER_SIZE
+    } else {
+        0
+    };
+    let end = start + (size - footer) as u64;
+    let mut position = start + id3::HEADER_SIZE as u64;
+    if extended {
+        let mut size = [0; 4];
+        reader.read_exact(&mut size)?;
+        position += match version {
+            3 => 4 + u32::from_be_bytes(size) as u64,
+            _ => match id3::syncsafe(&size) {
+                Some(size) => size as u64,
+                None => return Ok(()),
+            },
+        };
+    }
+
+    let header_len = id3::frame_header_len(version);
+    while position + header_len as u64 <= end {
+        let mut header = [0; 10];
+        reader.seek(SeekFrom::Start(position))?;
+        if id3::read_full(reader, &mut header[..header_len])? < header_len {
+            break;
+        }
+        let (id, size, flags) = match id3::parse_frame_header(version, &header) {
+            Some(header) => header,
+            None => break,
+        };
+        let body_start = position + header_len as u64;
+        position = body_start + size as u64;
+        if position > end {
+            break;
+        }
+        if id != "APIC" {
+            continue;
+        }
+
+        let format = FrameFormat::new(version, unsynchronised, flags);
+        if format.encrypted {
+            continue;
+        }
+        if format.compressed || format.unsynchronised {
+            let mut body = vec![0; size];
+            reader.seek(SeekFrom::Start(body_start))?;
+            reader.read_exact(&mut body)?;
+            let picture = id3::frame_body(version, unsynchronised, flags, &body).and_then(|body| {
+                let (mut picture, len) = id3::parse_picture_header(version, &body)?;
+                picture.data = body[len..].to_vec();
+                Some(picture)
+            });
+            pictures.extend(picture.map(EmbeddedPicture::from));
+            continue;
+        }
+
+        let data_start = body_start + format.extra as u64;
+        let data_size = size.saturating_sub(format.extra);
+        if let Some(picture) = read_picture_header(reader, version, data_start, data_size)? {
+            pictures.push(picture);
+        }
+    }
+    Ok(())
+}
+
+// Reads the fields of the picture frame with `size` bytes of data at
+// `start`, growing the read until they fit.
+fn read_picture_header<R: Read + Seek>(
+    reader: &mut R,
+    version: u8,
+    start: u64,
+    size: usize,
+) -> io::Result<Option<EmbeddedPicture>> {
+    let mut read_len = PICTURE_HEADER_SIZE;
+    loop {
+        let len = read_len.min(size);
+        let mut fields = vec![0; len];
+        reader.seek(SeekFrom::Start(start))?;
+        reader.read_exact(&mut fields)?;
+        // The description may be cut off unless image data follows it.
+        match id3::parse_picture_header(version, &fields) {
+            Some((picture, header_len)) if header_len < len || len == size => {
+                return Ok(Some(EmbeddedPicture {
+                    mime_type: picture.mime_type,
+                    picture_type: picture.picture_type,
+                    description: picture.description,
+                    size: (size - header_len) as u64,
+                    data: Data::Stream(start + header_len as u64),
+                }));
+            }
+            _ if len < size => read_len *= 4,
+            _ => return Ok(None),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{
+    buffered::BufferedDecoder,
+    gain::{GainOptions, ReplayGain},
+    id3::Tag,
+    into_frame, Error, Frame, FrameInfo, FrameRef, Sample, MAX_SAMPLES_PER_FRAME,
+};
+
+/// A MP3 decoder without a reader: bytes are fed to it as they arrive, and
+/// frames are returned as soon as they are complete.
+///
+/// Useful when the input comes from callbacks (network packets, channels)
+/// rather than an `io::Read`.
+///
+/// ```no_run
+/// use minimp3_fixed::{Error, PushDecoder};
+///
+/// # fn on_packet(packet: &[u8], decoder: &mut PushDecoder) {
+/// decoder.feed(packet);
+/// loop {
+///     match decoder.next_frame() {
+///         Ok(frame) => println!("Decoded {} samples", frame.data.len()),
+///         // Wait for the next packet
+///         Err(Error::InsufficientData) => break,
+///         Err(e) => panic!("{:?}", e),
+///     }
+/// }
+/// # }
+/// ```
+pub struct PushDecoder<S = i16> {
+    decoder: BufferedDecoder<S>,
+}
+
+impl PushDecoder {
+    /// Creates a new push decoder.
+    pub fn new() -> Self {
+        Self {
+            decoder: BufferedDecoder::eager(),
+        }
+    }
+}
+
+impl Default for PushDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PushDecoder<f32> {
+    /// Creates a new push decoder producing `f32` samples.
+    pub fn new_float() -> Self {
+        Self {
+            decoder: BufferedDecoder::eager(),
+        }
+    }
+}
+
+impl<S: Sample> PushDecoder<S> {
+    /// Appends `bytes` to the input.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        // An empty push would mark the end of the input.
+        if !bytes.is_empty() {
+            self.decoder.push(bytes);
+        }
+    }
+
+    /// Marks the end of the input, so a frame at the very end is decoded
+    /// without waiting for the header of the next frame. Feeding more bytes
+    /// afterwards resumes decoding.
+    pub fn finish(&mut self) {
+        self.decoder.push(&[]);
+    }
+
+    /// The number of fed bytes which haven't been decoded yet.
+    pub fn buffered_bytes(&self) -> usize {
+        self.decoder.buffered()
+    }
+
+    /// The ID3v2 tag at the start of the input, if it has one. Known once
+    /// the first frame was decoded. Tags larger than 8 MiB are skipped
+    /// without being parsed.
+    pub fn tag(&self) -> Option<&Tag> {
+        self.decoder.tag()
+    }
+
+    /// Applies ReplayGain to the decoded audio, or stops applying it with
+    /// `None`. Off by default.
+    pub fn set_replay_gain(&mut self, options: Option<GainOptions>) {
+        self.decoder.set_replay_gain(options);
+    }
+
+    /// The ReplayGain values found in the ID3v2 tag and the LAME tag. Known
+    /// once the first frame was decoded.
+    pub fn replay_gain(&self) -> ReplayGain {
+        self.decoder.replay_gain()
+    }
+
+    /// Decodes the next complete frame. Returns
+    /// [`Error::InsufficientData`](Error::InsufficientData) if more bytes
+    /// have to be fed first, or [`Error::Eof`](Error::Eof) once the input was
+    /// [`finish`](PushDecoder::finish)ed and every frame was returned.
+    pub fn next_frame(&mut self) -> Result<Frame<S>, Error> {
+        let mut pcm = vec![S::default(); MAX_SAMPLES_PER_FRAME];
+        let info = self.next_frame_into(&mut pcm)?;
+        Ok(into_frame(pcm, info))
+    }
+
+    /// Like [`next_frame`](PushDecoder::next_frame), but borrows the audio
+    /// from a buffer owned by the decoder instead of allocating.
+    pub fn next_frame_ref(&mut self) -> Result<FrameRef<'_, S>, Error> {
+        let mut pcm = self.decoder.take_pcm();
+        let result = self.next_frame_into(&mut pcm);
+        self.decoder.put_pcm(pcm, result)
+    }
+
+    /// Like [`next_frame`](PushDecoder::next_frame), but decodes into `buf`.
+    /// The decoded samples are `buf[..info.samples]`.
+    ///
+    /// Panics if `buf` is shorter than
+    /// [`MAX_SAMPLES_PER_FRAME`](MAX_SAMPLES_PER_FRAME).
+    pub fn next_frame_into(&mut self, buf: &mut [S]) -> Result<FrameInfo, Error> {
+        self.decoder.poll_frame(buf)?.ok_or(Error::InsufficientData)
+    }
+}
@@ -0,0 +1,245 @@
+use crate::ffi;
+use std::{
+    fmt::Debug,
+    os::raw::{c_int, c_void},
+};
+
+/// A PCM sample type the decoders can produce.
+///
+/// Implemented for `i16`, and for `f32` which is decoded by a second build of
+/// minimp3 compiled with `MINIMP3_FLOAT_OUTPUT`. Float samples are nominally
+/// in `-1.0..=1.0`, but are not clipped, so they keep the headroom the
+/// decoder had internally.
+pub trait Sample:
+    private::Sealed + Copy + Default + Debug + PartialEq + Send + Sync + Unpin + 'static
+{
+}
+
+impl Sample for i16 {}
+impl Sample for f32 {}
+
+pub(crate) mod private {
+    use super::*;
+
+    // Dispatches to the minimp3 build matching the sample type.
+    pub trait Sealed: Sized {
+        type Ex;
+        type FileInfo;
+
+        unsafe fn decode_frame(
+            dec: *mut ffi::mp3dec_t,
+            mp3: *const u8,
+            mp3_bytes: c_int,
+            pcm: *mut Self,
+            info: *mut ffi::mp3dec_frame_info_t,
+        ) -> c_int;
+        unsafe fn ex_open_cb(ex: *mut Self::Ex, io: *mut ffi::mp3dec_io_t, flags: c_int) -> c_int;
+        unsafe fn ex_open_buf(
+            ex: *mut Self::Ex,
+            buf: *const u8,
+            size: usize,
+            flags: c_int,
+        ) -> c_int;
+        unsafe fn ex_close(ex: *mut Self::Ex);
+        unsafe fn ex_seek(ex: *mut Self::Ex, position: u64) -> c_int;
+        unsafe fn ex_read_frame(
+            ex: *mut Self::Ex,
+            buf: *mut *mut Self,
+            info: *mut ffi::mp3dec_frame_info_t,
+            max_samples: u64,
+        ) -> u64;
+        unsafe fn ex_read(ex: *mut Self::Ex, buf: *mut Self, samples: u64) -> u64;
+
+        #[allow(clippy::too_many_arguments)]
+        unsafe fn load_cb(
+            dec: *mut ffi::mp3dec_t,
+            io: *mut ffi::mp3dec_io_t,
+            buf: *mut u8,
+            buf_size: usize,
+            info: *mut Self::FileInfo,
+            progress_cb: ffi::MP3D_PROGRESS_CB,
+            user_data: *mut c_void,
+        ) -> c_int;
+        fn file_info(info: &Self::FileInfo) -> FileInfo<Self>;
+
+        fn ex_info(ex: &Self::Ex) -> &ffi::mp3dec_frame_info_t;
+        fn ex_last_error(ex: &Self::Ex) -> c_int;
+        fn ex_samples(ex: &Self::Ex) -> u64;
+        fn ex_start_offset(ex: &Self::Ex) -> u64;
+        // Where the last frame `ex_read_frame` decoded ends: its offset in
+        // the stream, and a pointer past it in minimp3's read buffer.
+        fn ex_frame_end(ex: &Self::Ex) -> (u64, *const u8);
+        fn ex_index_state(ex: &mut Self::Ex) -> IndexState<'_>;
+
+        // Multiplies the sample by `factor`, clamping it to full scale if
+        // `clamp`. Integer samples are always clamped.
+        fn scale(self, factor: f32, clamp: bool) -> Self;
+    }
+
+    // The `mp3dec_file_info_t` of either build.
+    pub struct FileInfo<S> {
+        pub buffer: *mut S,
+        pub samples: usize,
+        pub channels: c_int,
+        pub hz: c_int,
+        pub layer: c_int,
+        pub avg_bitrate_kbps: c_int,
+    }
+
+    // The fields of the ex decoder describing its seek index.
+    pub struct IndexState<'a> {
+        pub index: &'a mut ffi::mp3dec_index_t,
+        pub indexes_built: &'a mut c_int,
+        pub samples: &'a mut u64,
+        pub detected_samples: &'a mut u64,
+        pub start_delay: c_int,
+    }
+
+    fn scale_i16(sample: i16, factor: f32, _clamp: bool) -> i16 {
+        // Float to integer casts saturate.
+        (sample as f32 * factor).round() as i16
+    }
+
+    fn scale_f32(sample: f32, factor: f32, clamp: bool) -> f32 {
+        let sample = sample * factor;
+        if clamp {
+            sample.clamp(-1.0, 1.0)
+        } else {
+            sample
+        }
+    }
+
+    macro_rules! impl_sealed {
+        ($sample:ty, $ffi:ident, $scale:ident) => {
+            impl Sealed for $sample {
+                type Ex = $ffi::mp3dec_ex_t;
+                type FileInfo = $ffi::mp3dec_file_info_t;
+
+                unsafe fn decode_frame(
+                    dec: *mut ffi::mp3dec_t,
+                    mp3: *const u8,
+                    mp3_bytes: c_int,
+                    pcm: *mut Self,
+                    info: *mut ffi::mp3dec_frame_info_t,
+                ) -> c_int {
+                    $ffi::mp3dec_decode_frame(dec, mp3, mp3_bytes, pcm, info)
+                }
+
+                unsafe fn ex_open_cb(
+                    ex: *mut Self::Ex,
+                    io: *mut ffi::mp3dec_io_t,
+                    flags: c_int,
+                ) -> c_int {
+                    $ffi::mp3dec_ex_open_cb(ex, io, flags)
+                }
+
+                unsafe fn ex_open_buf(
+                    ex: *mut Self::Ex,
+                    buf: *const u8,
+                    size: usize,
+                    flags: c_int,
+                ) -> c_int {
+                    $ffi::mp3dec_ex_open_buf(ex, buf, size as ffi::size_t, flags)
+                }
+
+                unsafe fn ex_close(ex: *mut Self::Ex) {
+                    $ffi::mp3dec_ex_close(ex)
+                }
+
+                unsafe fn ex_seek(ex: *mut Self::Ex, position: u64) -> c_int {
+                    $ffi::mp3dec_ex_seek(ex, position)
+                }
+
+                unsafe fn ex_read_frame(
+                    ex: *mut Self::Ex,
+                    buf: *mut *mut Self,
+                    info: *mut ffi::mp3dec_frame_info_t,
+                    max_samples: u64,
+                ) -> u64 {
+                    $ffi::mp3dec_ex_read_frame(ex, buf, info, max_samples)
+                }
+
+                unsafe fn ex_read(ex: *mut Self::Ex, buf: *mut Self, samples: u64) -> u64 {
+                    $ffi::mp3dec_ex_read(ex, buf, samples)
+                }
+
+                unsafe fn load_cb(
+                    dec: *mut ffi::mp3dec_t,
+                    io: *mut ffi::mp3dec_io_t,
+                    buf: *mut u8,
+                    buf_size: usize,
+                    info: *mut Self::FileInfo,
+                    progress_cb: ffi::MP3D_PROGRESS_CB,
+                    user_data: *mut c_void,
+                ) -> c_int {
+                    $ffi::mp3dec_load_cb(
+                        dec,
+                        io,
+                        buf,
+                        buf_size as ffi::size_t,
+                        info,
+                        progress_cb,
+                        user_data,
+                    )
+                }
+
+                fn file_info(info: &Self::FileInfo) -> FileInfo<Self> {
+                    FileInfo {
+                        buffer: info.buffer,
+                        samples: info.samples as usize,
+                        channels: info.channels,
+                        hz: info.hz,
+                        layer: info.layer,
+                        avg_bitrate_kbps: info.avg_bitrate_kbps,
+                    }
+                }
+
+                fn ex_info(ex: &Self::Ex) -> &ffi::mp3dec_frame_info_t {
+                    &ex.info
+                }
+
+                fn ex_last_error(ex: &Self::Ex) -> c_int {
+                    ex.last_error
+                }
+
+                fn ex_samples(ex: &Self::Ex) -> u64 {
+                    ex.samples
+                }
+
+                fn ex_start_offset(ex: &Self::Ex) -> u64 {
+                    ex.start_offset
+                }
+
+                fn ex_frame_end(ex: &Self::Ex) -> (u64, *const u8) {
+                    // Streams read through callbacks are decoded from a
+                    // buffer holding part of them, in memory ones in place.
+                    let consumed = if ex.io.is_null() {
+                        ex.offset as usize
+                    } else {
+                        ex.input_consumed as usize
+                    };
+                    (ex.offset, ex.file.buffer.wrapping_add(consumed))
+                }
+
+                fn ex_index_state(ex: &mut Self::Ex) -> IndexState<'_> {
+                    IndexState {
+                        index: &mut ex.index,
+                        indexes_built: &mut ex.indexes_built,
+                        samples: &mut ex.samples,
+                        detected_samples: &mut ex.detected_samples,
+                        start_delay: ex.start_delay,
+                    }
+                }
+
+                fn scale(self, factor: f32, clamp: bool) -> Self {
+                    $scale(self, factor, clamp)
+                }
+            }
+        };
+    }
+
+    use ffi::f32 as ffi_f32;
+
+    impl_sealed!(i16, ffi, scale_i16);
+    impl_sealed!(f32, ffi_f32, scale_f32);
+}
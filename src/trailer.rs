@@ -0,0 +1,133 @@
+//! Detection of the tags appended to the end of a stream: ID3v1 with
+//! Enhanced TAG+, APEv1/APEv2 and ID3v2.4 tags with a footer.
+//!
+//! The decoders read them from seekable readers and stop decoding where they
+//! start, see [`Decoder::read_trailer`](crate::Decoder::read_trailer) and
+//! [`SeekDecoder::trailer`](crate::SeekDecoder::trailer).
+//!
+//! ```no_run
+//! use minimp3_fixed::trailer::Trailer;
+//!
+//! let mut file = std::fs::File::open("file.mp3").unwrap();
+//! let trailer = Trailer::read(&mut file).unwrap();
+//! if let Some(tag) = trailer.id3v1 {
+//!     println!("{} ({:?})", tag.title(), tag.genre_name());
+//! }
+//! ```
+
+use crate::{
+    ape::{self, ApeTag},
+    id3::{self, EnhancedTag, Tag, V1Tag, ENHANCED_SIZE, V1_SIZE},
+};
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// How many bytes to read from the end of a stream at first. Enough for the
+/// fixed size tags and the footers of the others.
+pub const TAIL_SIZE: usize = 4096;
+
+/// The tags at the end of a stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trailer {
+    /// The stream offset the tags start at, which is the end of the audio.
+    pub start: u64,
+    pub id3v1: Option<V1Tag>,
+    pub ape: Option<ApeTag>,
+    pub id3v2: Option<Tag>,
+}
+
+impl Trailer {
+    /// Finds the tags at the end of a stream `len` bytes long, of which
+    /// `tail` are the last bytes. Returns `Err(start)` if the tags reach
+    /// further back than `tail`, and the bytes from offset `start` on are
+    /// needed.
+    pub fn parse(tail: &[u8], len: u64) -> Result<Trailer, u64> {
+        let tail_start = len.saturating_sub(tail.len() as u64);
+        let tail = &tail[tail.len() - (len - tail_start) as usize..];
+        let at = |start: u64, end: u64| match start.checked_sub(tail_start) {
+            Some(from) => Ok(&tail[from as usize..(end - tail_start) as usize]),
+            None => Err(start),
+        };
+
+        let mut trailer = Trailer {
+            start: len,
+            id3v1: None,
+            ape: None,
+            id3v2: None,
+        };
+        if let Some(start) = len.checked_sub(V1_SIZE as u64) {
+            if let Some(mut tag) = V1Tag::parse(at(start, len)?) {
+                trailer.start = start;
+                if let Some(start) = start.checked_sub(ENHANCED_SIZE as u64) {
+                    tag.enhanced = EnhancedTag::parse(at(start, trailer.start)?);
+                    if tag.enhanced.is_some() {
+                        trailer.start = start;
+                    }
+                }
+                trailer.id3v1 = Some(tag);
+            }
+        }
+
+        // APE and ID3v2 tags come in either order.
+        loop {
+            let end = trailer.start;
+            if trailer.ape.is_none() {
+                if let Some(start) = footer(end, ape::FOOTER_SIZE, ape::tag_size, at)? {
+                    trailer.ape = ApeTag::parse(at(start, end)?);
+                    trailer.start = start;
+                    continue;
+                }
+            }
+            if trailer.id3v2.is_none() {
+                let size = id3::footer_tag_size;
+                if let Some(start) = footer(end, id3::HEADER_SIZE, size, at)? {
+                    trailer.id3v2 = Tag::parse(at(start, end)?);
+                    trailer.start = start;
+                    continue;
+                }
+            }
+            return Ok(trailer);
+        }
+    }
+
+    /// Reads the tags at the end of `reader`. The position of `reader` is
+    /// left anywhere.
+    pub fn read<R: Read + Seek>(reader: &mut R) -> io::Result<Trailer> {
+        let len = reader.seek(SeekFrom::End(0))?;
+        let mut tail = Vec::new();
+        loop {
+            match Trailer::step(&tail, len) {
+                Ok(trailer) => return Ok(trailer),
+                Err(start) => {
+                    tail = vec![0; (len - start) as usize];
+                    reader.seek(SeekFrom::Start(start))?;
+                    reader.read_exact(&mut tail)?;
+                }
+            }
+        }
+    }
+
+    // A step of reading the tags of a stream `len` bytes long, given the
+    // last bytes of it read so far, none at first. Returns the tags, or
+    // `Err(start)` if the bytes from offset `start` to the end are to be read
+    // next.
+    pub(crate) fn step(tail: &[u8], len: u64) -> Result<Trailer, u64> {
+        if tail.is_empty() && len > 0 {
+            return Err(len.saturating_sub(TAIL_SIZE as u64));
+        }
+        Trailer::parse(tail, len)
+    }
+}
+
+// The start of the tag whose footer ends at `end`, if there is one.
+fn footer<'a>(
+    end: u64,
+    footer_size: usize,
+    tag_size: fn(&[u8]) -> Option<usize>,
+    at: impl Fn(u64, u64) -> Result<&'a [u8], u64>,
+) -> Result<Option<u64>, u64> {
+    let footer_start = match end.checked_sub(footer_size as u64) {
+        Some(start) => start,
+        None => return Ok(None),
+    };
+    Ok(tag_size(at(footer_start, end)?).and_then(|size| end.checked_sub(size as u64)))
+}
@@ -0,0 +1,293 @@
+//! Parsing of the Xing/Info and VBRI headers some encoders write in place of
+//! the audio of the first frame, to give the length of the stream, and of the
+//! LAME tag following a Xing/Info header.
+//!
+//! ```no_run
+//! use minimp3_fixed::vbr::VbrHeader;
+//!
+//! let data = std::fs::read("file.mp3").unwrap();
+//! if let Some(vbr) = VbrHeader::parse(&data) {
+//!     println!("{:?} frames", vbr.frames);
+//! }
+//! ```
+
+use crate::header::{ChannelMode, FrameHeader, Layer, Version, HEADER_SIZE};
+
+/// Which header was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VbrKind {
+    /// A Xing header, written for VBR streams.
+    Xing,
+    /// The same as `Xing`, but written by LAME for CBR streams.
+    Info,
+    /// A Fraunhofer VBRI header.
+    Vbri,
+}
+
+/// The table to seek in a stream with, from a [`VbrHeader`](VbrHeader).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SeekTable {
+    /// Entry `i` is the offset of the audio at `i`% of the duration, in
+    /// 1/256th of the stream size.
+    Xing(Box<[u8; 100]>),
+    /// Entry `i` is the size in bytes of the `i`th run of `frames_per_entry`
+    /// frames.
+    Vbri {
+        entries: Vec<u32>,
+        frames_per_entry: u32,
+    },
+}
+
+/// A parsed Xing/Info or VBRI header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VbrHeader {
+    pub kind: VbrKind,
+    /// The number of audio frames, not counting the frame holding this
+    /// header.
+    pub frames: Option<u32>,
+    /// The size of the stream in bytes.
+    pub bytes: Option<u32>,
+    pub seek_table: Option<SeekTable>,
+    /// The encoder quality. 0 is best for Xing headers, 100 for VBRI.
+    pub quality: Option<u32>,
+    /// The LAME tag after a Xing/Info header.
+    pub lame: Option<LameTag>,
+}
+
+/// The LAME extension of a Xing/Info header, also written by other
+/// encoders like FFmpeg.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LameTag {
+    /// The encoder name and version, like `LAME3.100`.
+    pub encoder: String,
+    pub revision: u8,
+    pub vbr_method: u8,
+    /// The lowpass filter frequency in hertz.
+    pub lowpass: Option<u32>,
+    /// The peak amplitude, 1.0 being full scale.
+    pub peak: Option<f32>,
+    /// The ReplayGain track (radio) gain in dB.
+    pub track_gain: Option<f32>,
+    /// The ReplayGain album (audiophile) gain in dB.
+    pub album_gain: Option<f32>,
+    /// The number of samples per channel the encoder added at the start, not
+    /// counting the 529 samples of delay the decoder adds.
+    pub encoder_delay: u16,
+    /// The number of samples per channel the encoder added at the end.
+    pub padding: u16,
+    /// The size in bytes of the stream, from the Xing/Info frame to the end
+    /// of the audio.
+    pub music_length: u32,
+    /// The CRC-16 of the audio frames.
+    pub music_crc: u16,
+}
+
+impl LameTag {
+    /// The delay of every MP3 decoder, in samples per channel.
+    pub const DECODER_DELAY: u64 = 529;
+
+    /// The samples per channel trimmed from the decoded stream for gapless
+    /// playback, after dropping the frame holding the tag: at the start the
+    /// encoder delay and the decoder delay, at the end the padding less the
+    /// decoder delay, which moved that much of it past the last frame. Both
+    /// [`Decoder`](crate::Decoder) and [`SeekDecoder`](crate::SeekDecoder)
+    /// trim this, like minimp3.
+    pub fn trimmed_samples(&self) -> (u64, u64) {
+        (
+            self.encoder_delay as u64 + Self::DECODER_DELAY,
+            (self.padding as u64).saturating_sub(Self::DECODER_DELAY),
+        )
+    }
+}
+
+const XING_FRAMES: u32 = 0x1;
+const XING_BYTES: u32 = 0x2;
+const XING_TOC: u32 = 0x4;
+const XING_QUALITY: u32 = 0x8;
+
+const LAME_TAG_SIZE: usize = 36;
+
+// VBRI headers always sit right after 32 bytes of side information.
+const VBRI_OFFSET: usize = HEADER_SIZE + 32;
+
+impl VbrHeader {
+    /// Parses the header from `frame`, which starts with a frame header.
+    /// Returns `None` if the frame doesn't hold a VBR header.
+    pub fn parse(frame: &[u8]) -> Option<VbrHeader> {
+        let header = FrameHeader::parse(frame)?;
+        if header.layer != Layer::Layer3 {
+            return None;
+        }
+        let frame = match header.frame_length() {
+            Some(length) => frame.get(..length).unwrap_or(frame),
+            None => frame,
+        };
+        parse_xing(&header, frame).or_else(|| parse_vbri(frame))
+    }
+
+    /// The number of samples of the stream, counting every channel, as
+    /// given by the frame count. `header` is the header of the first frame.
+    pub fn total_samples(&self, header: &FrameHeader) -> Option<u64> {
+        let frames = self.frames? as u64;
+        Some(frames * (header.samples_per_frame() * header.channels()) as u64)
+    }
+}
+
+fn parse_xing(header: &FrameHeader, frame: &[u8]) -> Option<VbrHeader> {
+    let side_info = match (header.version, header.channel_mode) {
+        (Version::Mpeg1, ChannelMode::Mono) => 17,
+        (Version::Mpeg1, _) => 32,
+        (_, ChannelMode::Mono) => 9,
+        (_, _) => 17,
+    };
+    let crc = if header.crc_protected { 2 } else { 0 };
+    let mut reader = Reader(frame.get(HEADER_SIZE + crc + side_info..)?);
+
+    let kind = match reader.bytes(4)? {
+        b"Xing" => VbrKind::Xing,
+        b"Info" => VbrKind::Info,
+        _ => return None,
+    };
+    let flags = reader.u32()?;
+    let mut field = |flag| match flags & flag {
+        0 => Some(None),
+        _ => reader.u32().map(Some),
+    };
+    let frames = field(XING_FRAMES)?;
+    let bytes = field(XING_BYTES)?;
+    let seek_table = match flags & XING_TOC {
+        0 => None,
+        _ => {
+            let toc: [u8; 100] = reader.bytes(100)?.try_into().ok()?;
+            Some(SeekTable::Xing(Box::new(toc)))
+        }
+    };
+    let quality = match flags & XING_QUALITY {
+        0 => None,
+        _ => Some(reader.u32()?),
+    };
+    let lame = reader.bytes(LAME_TAG_SIZE).and_then(parse_lame);
+
+    Some(VbrHeader {
+        kind,
+        frames,
+        bytes,
+        seek_table,
+        quality,
+        lame,
+    })
+}
+
+fn parse_lame(tag: &[u8]) -> Option<LameTag> {
+    let mut reader = Reader(tag);
+    let encoder = reader.bytes(9)?;
+    // Like minimp3, take any encoder name as the sign of a tag.
+    if encoder[0] == 0 {
+        return None;
+    }
+    let encoder = String::from_utf8_lossy(encoder)
+        .trim_end_matches(['\0', ' '])
+        .to_owned();
+    let revision = reader.bytes(1)?[0];
+    let lowpass = reader.bytes(1)?[0];
+    let peak = reader.u32()?;
+    let gains = [reader.u16()?, reader.u16()?];
+    let _flags = reader.u16()?;
+    let delay = reader.bytes(3)?;
+    let _misc = reader.u16()?;
+    let _preset = reader.u16()?;
+    let music_length = reader.u32()?;
+    let music_crc = reader.u16()?;
+
+    let gain = |name| {
+        let field = gains.iter().find(|&&field| field >> 13 == name)?;
+        let gain = (field & 0x1FF) as f32 / 10.0;
+        Some(if field & 0x200 != 0 { -gain } else { gain })
+    };
+
+    Some(LameTag {
+        encoder,
+        revision: revision >> 4,
+        vbr_method: revision & 0xF,
+        lowpass: match lowpass {
+            0 => None,
+            lowpass => Some(lowpass as u32 * 100),
+        },
+        // A 9.23 fixed point number.
+        peak: match peak {
+            0 => None,
+            peak => Some(peak as f32 / (1 << 23) as f32),
+        },
+        track_gain: gain(1),
+        album_gain: gain(2),
+        encoder_delay: (delay[0] as u16) << 4 | (delay[1] >> 4) as u16,
+        padding: ((delay[1] & 0xF) as u16) << 8 | delay[2] as u16,
+        music_length,
+        music_crc,
+    })
+}
+
+fn parse_vbri(frame: &[u8]) -> Option<VbrHeader> {
+    let mut reader = Reader(frame.get(VBRI_OFFSET..)?);
+    if reader.bytes(4)? != b"VBRI" {
+        return None;
+    }
+    let _version = reader.u16()?;
+    let _delay = reader.u16()?;
+    let quality = reader.u16()?;
+    let bytes = reader.u32()?;
+    let frames = reader.u32()?;
+    let entry_count = reader.u16()?;
+    let scale = reader.u16()? as u32;
+    let entry_size = reader.u16()?;
+    let frames_per_entry = reader.u16()? as u32;
+
+    let entries = (0..entry_count)
+        .map(|_| {
+            let entry = match entry_size {
+                1 => reader.bytes(1)?[0] as u32,
+                2 => reader.u16()? as u32,
+                3 => {
+                    let b = reader.bytes(3)?;
+                    u32::from_be_bytes([0, b[0], b[1], b[2]])
+                }
+                4 => reader.u32()?,
+                _ => return None,
+            };
+            Some(entry.saturating_mul(scale))
+        })
+        .collect::<Option<_>>()?;
+
+    Some(VbrHeader {
+        kind: VbrKind::Vbri,
+        frames: Some(frames),
+        bytes: Some(bytes),
+        seek_table: Some(SeekTable::Vbri {
+            entries,
+            frames_per_entry,
+        }),
+        quality: Some(quality as u32),
+        lame: None,
+    })
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(bytes)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_be_bytes(self.bytes(2)?.try_into().ok()?))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_be_bytes(self.bytes(4)?.try_into().ok()?))
+    }
+}
@@ -0,0 +1,200 @@
+//! One test suite, run against every IO adapter of `Decoder`.
+
+mod common;
+
+use common::VECTOR;
+use minimp3_fixed::{Decoder, Error, Frame};
+
+use std::io::{self, Read};
+
+/// Serves `data` in reads of at most `chunk` bytes, failing once `fail_at`
+/// bytes have been served.
+pub struct Chunked {
+    data: Vec<u8>,
+    position: usize,
+    chunk: usize,
+    fail_at: Option<usize>,
+}
+
+impl Chunked {
+    pub fn new(data: Vec<u8>, chunk: usize) -> Self {
+        Chunked {
+            data,
+            position: 0,
+            chunk,
+            fail_at: None,
+        }
+    }
+
+    pub fn failing_at(mut self, position: usize) -> Self {
+        self.fail_at = Some(position);
+        self
+    }
+}
+
+impl Read for Chunked {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.fail_at.is_some_and(|at| self.position >= at) {
+            return Err(io::Error::other("read failed"));
+        }
+        let end = (self.position + self.chunk.min(buf.len())).min(self.data.len());
+        let read = end - self.position;
+        buf[..read].copy_from_slice(&self.data[self.position..end]);
+        self.position = end;
+        Ok(read)
+    }
+}
+
+/// Decodes every frame of `reader`, stopping at the first error.
+pub type DecodeAll = fn(Chunked) -> (Vec<Frame>, Error);
+
+pub fn decode_blocking(reader: Chunked) -> (Vec<Frame>, Error) {
+    let mut decoder = Decoder::new(reader);
+    let mut frames = Vec::new();
+    loop {
+        match decoder.next_frame() {
+            Ok(frame) => frames.push(frame),
+            Err(e) => return (frames, e),
+        }
+    }
+}
+
+#[cfg(feature = "async_tokio")]
+impl tokio::io::AsyncRead for Chunked {
+    fn poll_read(
+        mut self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<io::Result<()>> {
+        let read = Read::read(&mut *self, buf.initialize_unfilled());
+        std::task::Poll::Ready(read.map(|read| buf.advance(read)))
+    }
+}
+
+#[cfg(feature = "async_tokio")]
+pub fn decode_tokio(reader: Chunked) -> (Vec<Frame>, Error) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let mut decoder = Decoder::new(reader);
+        let mut frames = Vec::new();
+        loop {
+            match decoder.next_frame_future().await {
+                Ok(frame) => frames.push(frame),
+                Err(e) => return (frames, e),
+            }
+        }
+    })
+}
+
+#[cfg(feature = "async_futures")]
+impl futures::io::AsyncRead for Chunked {
+    fn poll_read(
+        mut self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> std::task::Poll<io::Result<usize>> {
+        std::task::Poll::Ready(Read::read(&mut *self, buf))
+    }
+}
+
+// Runs on the `futures` executor, without a tokio runtime.
+#[cfg(feature = "async_futures")]
+pub fn decode_futures(reader: Chunked) -> (Vec<Frame>, Error) {
+    futures::executor::block_on(async {
+        let mut decoder = Decoder::new(minimp3_fixed::FuturesIo::new(reader));
+        let mut frames = Vec::new();
+        loop {
+            match decoder.next_frame_future().await {
+                Ok(frame) => frames.push(frame),
+                Err(e) => return (frames, e),
+            }
+        }
+    })
+}
+
+fn vector() -> Vec<u8> {
+    std::fs::read(VECTOR).unwrap()
+}
+
+fn assert_same_frames(a: &[Frame], b: &[Frame]) {
+    assert_eq!(a.len(), b.len());
+    for (a, b) in a.iter().zip(b) {
+        assert_eq!(a.info(), b.info());
+        assert_eq!(a.data, b.data);
+    }
+}
+
+pub fn matches_blocking_reference(decode: DecodeAll) {
+    let (reference, _) = decode_blocking(Chunked::new(vector(), usize::MAX));
+    let (frames, err) = decode(Chunked::new(vector(), usize::MAX));
+    assert!(matches!(err, Error::Eof), "{:?}", err);
+    assert!(!frames.is_empty());
+    assert_same_frames(&reference, &frames);
+}
+
+pub fn short_reads(decode: DecodeAll) {
+    let (reference, _) = decode(Chunked::new(vector(), usize::MAX));
+    for &chunk in &[1, 7, 417, 4096] {
+        let (frames, err) = decode(Chunked::new(vector(), chunk));
+        assert!(matches!(err, Error::Eof), "{:?}", err);
+        assert_same_frames(&reference, &frames);
+    }
+}
+
+pub fn empty_input(decode: DecodeAll) {
+    let (frames, err) = decode(Chunked::new(Vec::new(), usize::MAX));
+    assert!(frames.is_empty());
+    assert!(matches!(err, Error::Eof), "{:?}", err);
+}
+
+pub fn garbage_only(decode: DecodeAll) {
+    let garbage = (0..64 * 1024).map(|i| (i * 7 % 251) as u8 & 0x7f).collect();
+    let (frames, err) = decode(Chunked::new(garbage, 1000));
+    assert!(frames.is_empty());
+    assert!(matches!(err, Error::Eof), "{:?}", err);
+}
+
+pub fn io_errors_are_reported(decode: DecodeAll) {
+    let (frames, err) = decode(Chunked::new(vector(), 512).failing_at(4096));
+    assert!(matches!(err, Error::Io(_)), "{:?}", err);
+    assert!(frames.len() < decode(Chunked::new(vector(), 512)).0.len());
+}
+
+macro_rules! adapter_suite {
+    ($name:ident, $decode:path) => {
+        mod $name {
+            #[test]
+            fn matches_blocking_reference() {
+                super::matches_blocking_reference($decode)
+            }
+
+            #[test]
+            fn short_reads() {
+                super::short_reads($decode)
+            }
+
+            #[test]
+            fn empty_input() {
+                super::empty_input($decode)
+            }
+
+            #[test]
+            fn garbage_only() {
+                super::garbage_only($decode)
+            }
+
+            #[test]
+            fn io_errors_are_reported() {
+                super::io_errors_are_reported($decode)
+            }
+        }
+    };
+}
+
+adapter_suite!(blocking, super::decode_blocking);
+#[cfg(feature = "async_tokio")]
+adapter_suite!(tokio_adapter, super::decode_tokio);
+#[cfg(feature = "async_futures")]
+adapter_suite!(futures_adapter, super::decode_futures);
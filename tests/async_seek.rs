@@ -0,0 +1,56 @@
+#![cfg(feature = "async_tokio")]
+
+mod common;
+
+use common::VECTOR;
+use minimp3_fixed::{AsyncSeekDecoder, Decoder, Error};
+
+use std::fs::File;
+
+fn decoded_samples() -> Vec<i16> {
+    let mut decoder = Decoder::new(File::open(VECTOR).unwrap());
+    let mut samples = Vec::new();
+    loop {
+        match decoder.next_frame() {
+            Ok(frame) => samples.extend(frame.data),
+            Err(Error::Eof) => return samples,
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+}
+
+async fn read_to_end(decoder: &mut AsyncSeekDecoder<tokio::fs::File>) -> Vec<i16> {
+    let mut samples = Vec::new();
+    let mut buf = [0; 1000];
+    loop {
+        match decoder.read_samples(&mut buf).await.unwrap() {
+            0 => return samples,
+            n => samples.extend_from_slice(&buf[..n]),
+        }
+    }
+}
+
+#[tokio::test]
+async fn decodes_like_decoder() {
+    let file = tokio::fs::File::open(VECTOR).await.unwrap();
+    let mut decoder = AsyncSeekDecoder::new(file).await.unwrap();
+
+    let expected = decoded_samples();
+    assert_eq!(decoder.samples(), expected.len() as u64);
+    assert_eq!(read_to_end(&mut decoder).await, expected);
+}
+
+#[tokio::test]
+async fn seek_skips_samples() {
+    let file = tokio::fs::File::open(VECTOR).await.unwrap();
+    let mut decoder = AsyncSeekDecoder::new(file).await.unwrap();
+    let expected = decoded_samples();
+
+    for &sample in &[100, 5000, expected.len() / 2, 0] {
+        decoder.seek_samples(sample as u64).await.unwrap();
+        assert_eq!(read_to_end(&mut decoder).await, &expected[sample..]);
+    }
+
+    decoder.seek_samples(expected.len() as u64).await.unwrap();
+    assert!(matches!(decoder.decode_frame().await, Err(Error::Eof)));
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use common::{frame, id3v2, latin1, VECTOR};
+use minimp3_fixed::id3::{Content, Tag};
+use minimp3_fixed::{Error, SeekDecoder};
+
+use std::io::Cursor;
+
+fn title(text: &str) -> Vec<u8> {
+    frame(3, "TIT2", 0, &latin1(text))
+}
+
+fn chapter(id: &str, start_ms: u32, end_ms: u32, name: &str) -> Vec<u8> {
+    let mut body = id.as_bytes().to_vec();
+    body.push(0);
+    for field in [start_ms, end_ms, u32::MAX, u32::MAX] {
+        body.extend(field.to_be_bytes());
+    }
+    body.extend(title(name));
+    frame(3, "CHAP", 0, &body)
+}
+
+fn toc(id: &str, flags: u8, children: &[&str]) -> Vec<u8> {
+    let mut body = id.as_bytes().to_vec();
+    body.extend([0, flags, children.len() as u8]);
+    for child in children {
+        body.extend(child.as_bytes());
+        body.push(0);
+    }
+    body.extend(title("Contents"));
+    frame(3, "CTOC", 0, &body)
+}
+
+fn chaptered() -> Vec<u8> {
+    let mut frames = chapter("intro", 0, 1000, "Intro");
+    frames.extend(chapter("part2", 1500, 3000, "Part 2"));
+    frames.extend(chapter("part1", 1000, 1500, "Part 1"));
+    frames.extend(toc("parts", 0x01, &["part1", "part2"]));
+    frames.extend(toc("root", 0x03, &["intro", "parts"]));
+    id3v2(3, 0, &frames)
+}
+
+#[test]
+fn parses_chapters() {
+    let tag = Tag::parse(&chaptered()).unwrap();
+
+    let chapters = tag.chapters();
+    let titles: Vec<_> = chapters.iter().map(|c| c.title().unwrap()).collect();
+    assert_eq!(titles, ["Intro", "Part 1", "Part 2"]);
+    assert_eq!(chapters[1].start_ms, 1000);
+    assert_eq!(chapters[1].end_ms, 1500);
+    assert_eq!(chapters[1].start_offset, None);
+    assert!(chapters[1].contains(1000));
+    assert!(!chapters[1].contains(1500));
+
+    let root = tag.tables_of_contents().find(|toc| toc.top_level).unwrap();
+    assert_eq!(root.element_id, "root");
+    assert!(root.ordered);
+    assert_eq!(root.children, ["intro", "parts"]);
+    assert_eq!(root.title(), Some("Contents"));
+
+    assert_eq!(tag.chapter_at(0), Some(0));
+    assert_eq!(tag.chapter_at(1499), Some(1));
+    assert_eq!(tag.chapter_at(1500), Some(2));
+    assert_eq!(tag.chapter_at(3000), None);
+}
+
+#[test]
+fn orders_chapters_by_time_without_toc() {
+    let mut frames = chapter("b", 500, 900, "B");
+    frames.extend(chapter("a", 0, 500, "A"));
+    let tag = Tag::parse(&id3v2(3, 0, &frames)).unwrap();
+    let titles: Vec<_> = tag.chapters().iter().map(|c| c.title().unwrap()).collect();
+    assert_eq!(titles, ["A", "B"]);
+    assert!(matches!(tag.frames[0].content, Content::Chapter(_)));
+}
+
+#[test]
+fn seeks_to_chapters() {
+    let data = [chaptered(), std::fs::read(VECTOR).unwrap()].concat();
+    let mut decoder = SeekDecoder::new(Cursor::new(data)).unwrap();
+    assert_eq!(decoder.chapters().len(), 3);
+    assert_eq!(decoder.current_chapter(), Some(0));
+
+    let rate = decoder.current_sample_rate() as u64;
+    let channels = decoder._current_channels() as u64;
+    decoder.seek_to_chapter(1).unwrap();
+    assert_eq!(decoder.current_chapter(), Some(1));
+    let frame = decoder.decode_frame().unwrap();
+    assert_eq!(frame.sample_position, rate * channels);
+
+    decoder.seek_to_chapter(2).unwrap();
+    assert_eq!(decoder.current_chapter(), Some(2));
+    assert!(matches!(
+        decoder.seek_to_chapter(3),
+        Err(Error::NoSuchChapter)
+    ));
+}
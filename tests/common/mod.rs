@@ -0,0 +1,107 @@
+//! Builders for the streams and tags the tests decode.
+
+// Every test binary uses a different part of this module.
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+pub const VECTOR: &str = "minimp3-sys/minimp3/vectors/M2L3_bitrate_24_all.bit";
+
+/// Every test vector of minimp3, sorted by name.
+pub fn vectors() -> Vec<PathBuf> {
+    let mut vectors: Vec<_> = std::fs::read_dir("minimp3-sys/minimp3/vectors")
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "bit"))
+        .collect();
+    vectors.sort();
+    vectors
+}
+
+pub fn syncsafe(size: usize) -> [u8; 4] {
+    [
+        (size >> 21) as u8 & 0x7F,
+        (size >> 14) as u8 & 0x7F,
+        (size >> 7) as u8 & 0x7F,
+        size as u8 & 0x7F,
+    ]
+}
+
+pub fn unsynchronise(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for &byte in data {
+        out.push(byte);
+        if byte == 0xFF {
+            out.push(0);
+        }
+    }
+    out
+}
+
+/// An ID3v2 frame of the given major `version`.
+pub fn frame(version: u8, id: &str, flags: u8, body: &[u8]) -> Vec<u8> {
+    let mut frame = id.as_bytes().to_vec();
+    match version {
+        2 => frame.extend(&(body.len() as u32).to_be_bytes()[1..]),
+        3 => frame.extend((body.len() as u32).to_be_bytes()),
+        _ => frame.extend(syncsafe(body.len())),
+    }
+    if version > 2 {
+        frame.extend([0, flags]);
+    }
+    frame.extend(body);
+    frame
+}
+
+/// An ID3v2 tag around `body`, which is taken as is.
+pub fn id3v2(version: u8, flags: u8, body: &[u8]) -> Vec<u8> {
+    let mut tag = vec![b'I', b'D', b'3', version, 0, flags];
+    tag.extend(syncsafe(body.len()));
+    tag.extend(body);
+    tag
+}
+
+/// The body of a text frame in ISO-8859-1.
+pub fn latin1(text: &str) -> Vec<u8> {
+    let mut body = vec![0];
+    body.extend(text.as_bytes());
+    body
+}
+
+/// An ID3v2.3 user defined text frame.
+pub fn txxx(description: &str, value: &str) -> Vec<u8> {
+    let mut body = latin1(description);
+    body.push(0);
+    body.extend(value.as_bytes());
+    frame(3, "TXXX", 0, &body)
+}
+
+/// An APEv2 tag of `(key, flags, value)` items, with a footer and, if
+/// `header` is set, a header.
+pub fn ape_tag(items: &[(&str, u32, &[u8])], header: bool) -> Vec<u8> {
+    let mut data = Vec::new();
+    for (key, flags, value) in items {
+        data.extend((value.len() as u32).to_le_bytes());
+        data.extend(flags.to_le_bytes());
+        data.extend(key.as_bytes());
+        data.push(0);
+        data.extend(*value);
+    }
+
+    let block = |flags: u32| {
+        let mut block = b"APETAGEX".to_vec();
+        block.extend(2000u32.to_le_bytes());
+        block.extend((data.len() as u32 + 32).to_le_bytes());
+        block.extend((items.len() as u32).to_le_bytes());
+        block.extend(flags.to_le_bytes());
+        block.extend([0; 8]);
+        block
+    };
+    let mut tag = Vec::new();
+    if header {
+        tag.extend(block(0xA000_0000));
+    }
+    tag.extend(&data);
+    tag.extend(block(if header { 0x8000_0000 } else { 0 }));
+    tag
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use common::vectors;
+use minimp3_fixed::{decode_all, DecodeProgress, DecodedAudio, Error, SeekDecoder};
+
+use std::fs::File;
+use std::io::Cursor;
+use std::ops::ControlFlow;
+
+#[test]
+fn decodes_like_seek_decoder() {
+    for path in vectors() {
+        let mut decoder = SeekDecoder::new(File::open(&path).unwrap()).unwrap();
+        let mut expected = vec![0; decoder.total_samples() as usize + 4096];
+        let len = decoder.read_samples(&mut expected).unwrap();
+        expected.truncate(len);
+
+        let audio = decode_all(File::open(&path).unwrap()).unwrap();
+        assert_eq!(audio.samples, expected, "{:?}", path);
+        if !expected.is_empty() {
+            assert_eq!(audio.channels, decoder._current_channels());
+            assert_eq!(audio.sample_rate, decoder.current_sample_rate());
+            assert_eq!(audio.layer, 3);
+            assert!(audio.avg_bitrate_kbps > 0);
+        }
+
+        let float = DecodedAudio::<f32>::decode(File::open(&path).unwrap(), None).unwrap();
+        assert_eq!(float.samples.len(), expected.len());
+    }
+}
+
+#[test]
+fn reports_progress() {
+    let path = &vectors()[0];
+    let data = std::fs::read(path).unwrap();
+    // Tags at the end aren't decoded.
+    let mut tagged = data.clone();
+    let mut id3v1 = b"TAG".to_vec();
+    id3v1.resize(128, 0);
+    tagged.extend(&id3v1);
+
+    let mut reports = Vec::new();
+    let mut record = |progress: DecodeProgress| {
+        reports.push(progress);
+        ControlFlow::Continue(())
+    };
+    let audio = DecodedAudio::<i16>::decode(Cursor::new(tagged), Some(&mut record)).unwrap();
+    assert_eq!(audio, decode_all(Cursor::new(data.clone())).unwrap());
+    assert!(!reports.is_empty());
+    assert!(reports.windows(2).all(|w| w[0].position <= w[1].position));
+    for report in &reports {
+        assert_eq!(report.size, data.len() as u64);
+        assert!(report.position <= report.size);
+    }
+}
+
+#[test]
+fn decodes_from_the_reader_position() {
+    let data = std::fs::read(&vectors()[0]).unwrap();
+    let mut embedded = vec![0x55; 1000];
+    embedded.extend(&data);
+    let mut reader = Cursor::new(embedded);
+    reader.set_position(1000);
+
+    let mut reports = Vec::new();
+    let mut record = |progress: DecodeProgress| {
+        reports.push(progress);
+        ControlFlow::Continue(())
+    };
+    let audio = DecodedAudio::<i16>::decode(reader, Some(&mut record)).unwrap();
+    assert_eq!(audio, decode_all(Cursor::new(data.clone())).unwrap());
+    let last = reports.last().unwrap();
+    assert_eq!(last.size, data.len() as u64);
+    assert!(last.position <= last.size);
+}
+
+#[test]
+fn cancels() {
+    let data = std::fs::read(&vectors()[0]).unwrap();
+    let mut calls = 0;
+    let mut cancel = |_| {
+        calls += 1;
+        if calls == 3 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    };
+    let result = DecodedAudio::<i16>::decode(Cursor::new(data), Some(&mut cancel));
+    assert!(matches!(result, Err(Error::Cancelled)));
+    assert_eq!(calls, 3);
+}
+
+#[test]
+#[should_panic(expected = "from the callback")]
+fn resumes_panics() {
+    let data = std::fs::read(&vectors()[0]).unwrap();
+    let mut panicking = |_| -> ControlFlow<()> { panic!("from the callback") };
+    let _ = DecodedAudio::<i16>::decode(Cursor::new(data), Some(&mut panicking));
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use common::VECTOR;
+use minimp3_fixed::{Decoder, Error, SeekDecoder};
+
+use std::fs::File;
+
+fn assert_close(float: &[f32], int: &[i16]) {
+    assert_eq!(float.len(), int.len());
+    for (&f, &i) in float.iter().zip(int) {
+        let scaled = (f * 32768.0).clamp(-32768.0, 32767.0);
+        assert!((scaled - i as f32).abs() <= 1.0, "{} vs {}", f, i);
+    }
+}
+
+#[test]
+fn decoder_float_matches_int() {
+    let mut int = Decoder::new(File::open(VECTOR).unwrap());
+    let mut float = Decoder::new_float(File::open(VECTOR).unwrap());
+    let mut frames = 0;
+
+    loop {
+        match (int.next_frame(), float.next_frame()) {
+            (Ok(i), Ok(f)) => {
+                assert_eq!(i.info(), f.info());
+                assert_close(&f.data, &i.data);
+                frames += 1;
+            }
+            (Err(Error::Eof), Err(Error::Eof)) => break,
+            (i, f) => panic!("{:?} {:?}", i.err(), f.err()),
+        }
+    }
+
+    assert!(frames > 0);
+}
+
+#[test]
+fn seek_decoder_float_matches_int() {
+    let mut int = SeekDecoder::new(File::open(VECTOR).unwrap()).unwrap();
+    let mut float = SeekDecoder::new_float(File::open(VECTOR).unwrap()).unwrap();
+    let mut int_buf = [0i16; 4096];
+    let mut float_buf = [0f32; 4096];
+
+    int.seek_samples(1000).unwrap();
+    float.seek_samples(1000).unwrap();
+
+    loop {
+        let i = int.read_samples(&mut int_buf).unwrap();
+        let f = float.read_samples(&mut float_buf).unwrap();
+        assert_close(&float_buf[..f], &int_buf[..i]);
+        if i == 0 {
+            break;
+        }
+    }
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::VECTOR;
+use minimp3_fixed::{Decoder, Error, SeekDecoder, MAX_SAMPLES_PER_FRAME};
+
+use std::fs::File;
+
+#[test]
+fn decoder_into_matches_owned_frames() {
+    let mut owned = Decoder::new(File::open(VECTOR).unwrap());
+    let mut borrowed = Decoder::new(File::open(VECTOR).unwrap());
+    let mut into = Decoder::new(File::open(VECTOR).unwrap());
+    let mut buf = [0; MAX_SAMPLES_PER_FRAME];
+
+    loop {
+        match owned.next_frame() {
+            Ok(frame) => {
+                let frame_ref = borrowed.next_frame_ref().unwrap();
+                assert_eq!(frame_ref.data, &frame.data[..]);
+                assert_eq!(frame_ref.info, frame.info());
+
+                let info = into.next_frame_into(&mut buf).unwrap();
+                assert_eq!(&buf[..info.samples], &frame.data[..]);
+                assert_eq!(info, frame.info());
+            }
+            Err(Error::Eof) => break,
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    assert!(matches!(borrowed.next_frame_ref(), Err(Error::Eof)));
+    assert!(matches!(into.next_frame_into(&mut buf), Err(Error::Eof)));
+}
+
+#[test]
+fn seek_decoder_into_matches_owned_frames() {
+    let mut owned = SeekDecoder::new(File::open(VECTOR).unwrap()).unwrap();
+    let mut into = SeekDecoder::new(File::open(VECTOR).unwrap()).unwrap();
+    let mut buf = [0; MAX_SAMPLES_PER_FRAME];
+
+    loop {
+        match owned.decode_frame() {
+            Ok(frame) => {
+                let info = into.next_frame_into(&mut buf).unwrap();
+                assert_eq!(&buf[..info.samples], &frame.data[..]);
+                assert_eq!(info, frame.info());
+            }
+            Err(Error::Eof) => break,
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    assert!(matches!(into.next_frame_ref(), Err(Error::Eof)));
+}
+
+#[test]
+fn seek_decoder_into_empty_buffer() {
+    let mut expected = SeekDecoder::new(File::open(VECTOR).unwrap()).unwrap();
+    let mut decoder = SeekDecoder::new(File::open(VECTOR).unwrap()).unwrap();
+    let mut buf = [0; MAX_SAMPLES_PER_FRAME];
+
+    assert!(matches!(
+        decoder.next_frame_into(&mut []),
+        Err(Error::InsufficientData)
+    ));
+    let info = decoder.next_frame_into(&mut buf).unwrap();
+    let frame = expected.decode_frame().unwrap();
+    assert_eq!(&buf[..info.samples], &frame.data[..]);
+}
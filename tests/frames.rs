@@ -0,0 +1,116 @@
+mod common;
+
+use common::{id3v2, vectors, VECTOR};
+use minimp3_fixed::{iterate_frames, iterate_frames_buf, Decoder, SeekDecoder};
+
+use std::fs::File;
+use std::io::Cursor;
+use std::ops::ControlFlow;
+
+// The offset and bytes of every frame.
+fn collect(data: &[u8]) -> Vec<(u64, &[u8])> {
+    let mut frames = Vec::new();
+    iterate_frames_buf(data, |frame| {
+        frames.push((frame.info.offset.unwrap(), frame.data));
+        ControlFlow::Continue(())
+    })
+    .unwrap();
+    frames
+}
+
+#[test]
+fn walks_frames() {
+    for path in vectors() {
+        let data = std::fs::read(&path).unwrap();
+        let frames = collect(&data);
+
+        let mut from_reader = Vec::new();
+        let mut end = 0;
+        let mut position = 0;
+        iterate_frames(File::open(&path).unwrap(), |frame| {
+            let info = frame.info;
+            let offset = info.offset.unwrap();
+            assert_eq!(offset, end + info.skipped_bytes as u64);
+            assert_eq!(info.frame_bytes, frame.data.len());
+            assert_eq!(info.sample_position, position);
+            assert_eq!(info.header.unwrap().channels(), info.channels);
+            assert_eq!(frame.data[0], 0xFF);
+            end = offset + frame.data.len() as u64;
+            position += info.samples as u64;
+            from_reader.push((offset, frame.data.to_vec()));
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        let from_buf: Vec<_> = frames
+            .iter()
+            .map(|&(offset, data)| (offset, data.to_vec()))
+            .collect();
+        assert_eq!(from_reader, from_buf, "{:?}", path);
+        for (offset, frame) in &frames {
+            let offset = *offset as usize;
+            assert_eq!(&data[offset..offset + frame.len()], *frame);
+        }
+
+        // Every decoded frame is found.
+        for frame in Decoder::new(Cursor::new(&data)) {
+            let offset = frame.unwrap().offset.unwrap();
+            assert!(frames.iter().any(|&(o, _)| o == offset), "{:?}", path);
+        }
+        let decoder = SeekDecoder::new(File::open(&path).unwrap()).unwrap();
+        assert!(position >= decoder.total_samples(), "{:?}", path);
+    }
+}
+
+#[test]
+fn skips_tags() {
+    let data = std::fs::read(VECTOR).unwrap();
+    let mut tagged = id3v2(3, 0, &[0; 16]);
+    tagged.extend(&data);
+    let mut id3v1 = b"TAG".to_vec();
+    id3v1.resize(128, 0xFF);
+    tagged.extend(&id3v1);
+
+    let plain = collect(&data);
+    let frames = collect(&tagged);
+    assert_eq!(frames.len(), plain.len());
+    assert_eq!(frames[0].0, plain[0].0 + 26);
+    let (offset, last) = frames.last().unwrap();
+    assert!(*offset as usize + last.len() <= tagged.len() - 128);
+
+    // The tag isn't counted as skipped, like in the decoders.
+    let first_skipped = |data: &[u8]| {
+        let mut skipped = None;
+        iterate_frames(Cursor::new(data), |frame| {
+            skipped = Some(frame.info.skipped_bytes);
+            ControlFlow::Break(())
+        })
+        .unwrap();
+        skipped.unwrap()
+    };
+    assert_eq!(first_skipped(&tagged), first_skipped(&data));
+    let decoded = Decoder::new(Cursor::new(&tagged)).next().unwrap().unwrap();
+    assert_eq!(first_skipped(&tagged), decoded.skipped_bytes);
+}
+
+#[test]
+fn stops_early() {
+    let data = std::fs::read(VECTOR).unwrap();
+    let mut calls = 0;
+    let result = iterate_frames(Cursor::new(&data), |_| {
+        calls += 1;
+        if calls == 3 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+    assert!(result.is_ok());
+    assert_eq!(calls, 3);
+}
+
+#[test]
+#[should_panic(expected = "from the callback")]
+fn resumes_panics() {
+    let data = std::fs::read(VECTOR).unwrap();
+    let _ = iterate_frames_buf(&data, |_| panic!("from the callback"));
+}
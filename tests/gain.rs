@@ -0,0 +1,217 @@
+mod common;
+
+use common::{ape_tag, id3v2, txxx, VECTOR};
+use minimp3_fixed::ape::ApeTag;
+use minimp3_fixed::gain::{GainMode, GainOptions, ReplayGain};
+use minimp3_fixed::id3::Tag;
+use minimp3_fixed::trailer::Trailer;
+use minimp3_fixed::vbr::LameTag;
+use minimp3_fixed::{Decoder, Error, PushDecoder, SeekDecoder};
+
+use std::io::Cursor;
+
+fn lame(track_gain: Option<f32>, album_gain: Option<f32>) -> LameTag {
+    LameTag {
+        encoder: "LAME3.100".to_owned(),
+        revision: 0,
+        vbr_method: 1,
+        lowpass: None,
+        peak: Some(0.9),
+        track_gain,
+        album_gain,
+        encoder_delay: 576,
+        padding: 0,
+        music_length: 0,
+        music_crc: 0,
+    }
+}
+
+#[test]
+fn finds_gains() {
+    let frames = [
+        txxx("REPLAYGAIN_TRACK_GAIN", "-6.50 dB"),
+        txxx("REPLAYGAIN_TRACK_PEAK", "0.988"),
+        txxx("replaygain_album_gain", "+2.25 dB"),
+    ];
+    let tag = Tag::parse(&id3v2(3, 0, &frames.concat())).unwrap();
+    let gain = ReplayGain::from_id3(&tag);
+    assert_eq!(gain.track_gain, Some(-6.5));
+    assert_eq!(gain.track_peak, Some(0.988));
+    assert_eq!(gain.album_gain, Some(2.25));
+    assert_eq!(gain.album_peak, None);
+
+    // R128 gains are relative to -23 LUFS.
+    let ape = ApeTag::parse(&ape_tag(
+        &[
+            ("R128_TRACK_GAIN", 0, b"-512"),
+            ("Replaygain_Album_Peak", 0, b"0.5"),
+        ],
+        false,
+    ))
+    .unwrap();
+    let gain = ReplayGain::from_ape(&ape);
+    assert_eq!(gain.track_gain, Some(3.0));
+    assert_eq!(gain.album_peak, Some(0.5));
+
+    let trailer = Trailer {
+        start: 0,
+        id3v1: None,
+        ape: Some(ape),
+        id3v2: None,
+    };
+    let lame = lame(Some(-1.0), Some(-2.0));
+    let gain = ReplayGain::find(Some(&tag), Some(&trailer), Some(&lame));
+    assert_eq!(
+        gain,
+        ReplayGain {
+            track_gain: Some(-6.5),
+            track_peak: Some(0.988),
+            album_gain: Some(2.25),
+            album_peak: Some(0.5),
+        }
+    );
+    assert_eq!(
+        ReplayGain::find(None, None, Some(&lame)).track_peak,
+        Some(0.9)
+    );
+    assert!(ReplayGain::find(None, None, None).is_empty());
+}
+
+#[test]
+fn prevents_clipping() {
+    let gain = ReplayGain {
+        track_gain: Some(-6.0),
+        track_peak: Some(1.0),
+        album_gain: Some(6.0),
+        album_peak: Some(0.8),
+    };
+    let track = GainOptions::default();
+    assert!((track.factor(&gain) - 0.501).abs() < 0.001);
+    let album = GainOptions {
+        mode: GainMode::Album,
+        ..track
+    };
+    assert_eq!(album.factor(&gain), 1.25);
+    let loud = GainOptions {
+        prevent_clipping: false,
+        ..album
+    };
+    assert!((loud.factor(&gain) - 1.995).abs() < 0.001);
+    let preamp = GainOptions {
+        preamp: -12.0,
+        ..album
+    };
+    assert!((preamp.factor(&gain) - 0.501).abs() < 0.001);
+
+    // Falls back to the other gain, or none.
+    let track_only = ReplayGain {
+        album_gain: None,
+        ..gain
+    };
+    assert_eq!(album.factor(&track_only), track.factor(&gain));
+    assert_eq!(track.factor(&ReplayGain::default()), 1.0);
+
+    // The peak limits even a gain of 0 dB.
+    let over_full_scale = ReplayGain {
+        track_gain: Some(0.0),
+        track_peak: Some(2.0),
+        ..ReplayGain::default()
+    };
+    assert_eq!(track.factor(&over_full_scale), 0.5);
+    let unlimited = GainOptions {
+        prevent_clipping: false,
+        ..track
+    };
+    assert_eq!(unlimited.factor(&over_full_scale), 1.0);
+}
+
+#[test]
+fn decoders_apply_gains() {
+    let audio = std::fs::read(VECTOR).unwrap();
+    let frames = [
+        txxx("REPLAYGAIN_TRACK_GAIN", "-6.02 dB"),
+        txxx("REPLAYGAIN_TRACK_PEAK", "0.25"),
+    ];
+    let tag = id3v2(3, 0, &frames.concat());
+    let data = [tag, audio.clone()].concat();
+
+    let mut reference = Vec::new();
+    let mut decoder = Decoder::new(Cursor::new(audio));
+    loop {
+        match decoder.next_frame() {
+            Ok(frame) => reference.extend(frame.data),
+            Err(Error::Eof) => break,
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    let options = GainOptions {
+        preamp: 24.0,
+        ..GainOptions::default()
+    };
+    let mut decoder = Decoder::new(Cursor::new(data.clone()));
+    decoder.set_replay_gain(Some(options));
+    let mut gained = Vec::new();
+    loop {
+        match decoder.next_frame() {
+            Ok(frame) => gained.extend(frame.data),
+            Err(Error::Eof) => break,
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+    // The peak limits the gain to 4.
+    assert_eq!(options.factor(&decoder.replay_gain()), 4.0);
+    let expected: Vec<i16> = reference
+        .iter()
+        .map(|&sample| (sample as f32 * 4.0).round() as i16)
+        .collect();
+    assert_eq!(gained, expected);
+
+    let mut decoder = PushDecoder::new();
+    decoder.set_replay_gain(Some(options));
+    decoder.feed(&data);
+    decoder.finish();
+    let mut pushed = Vec::new();
+    while let Ok(frame) = decoder.next_frame() {
+        pushed.extend(frame.data);
+    }
+    assert_eq!(pushed, expected);
+
+    let mut decoder = SeekDecoder::new(Cursor::new(data)).unwrap();
+    assert_eq!(decoder.replay_gain().track_peak, Some(0.25));
+    let mut plain = vec![0; 4096];
+    decoder.read_samples(&mut plain).unwrap();
+    decoder.seek_samples(0).unwrap();
+    decoder.set_replay_gain(Some(options));
+    let mut loud = vec![0; 4096];
+    decoder.read_samples(&mut loud).unwrap();
+    for (loud, plain) in loud.iter().zip(&plain) {
+        assert_eq!(*loud, (*plain as f32 * 4.0).round() as i16);
+    }
+    decoder.seek_samples(0).unwrap();
+    let frame = decoder.decode_frame().unwrap();
+    assert_eq!(frame.data[..], loud[..frame.data.len()]);
+}
+
+#[test]
+fn zero_gain_leaves_float_samples() {
+    // Float samples aren't clamped when no gain is applied, even when
+    // preventing clipping.
+    let audio = std::fs::read(VECTOR).unwrap();
+    let decode = |options| {
+        let mut decoder = Decoder::new_float(Cursor::new(audio.clone()));
+        decoder.set_replay_gain(options);
+        let mut samples = Vec::new();
+        loop {
+            match decoder.next_frame() {
+                Ok(frame) => samples.extend(frame.data),
+                Err(Error::Eof) => return samples,
+                Err(e) => panic!("{:?}", e),
+            }
+        }
+    };
+    let options = GainOptions::default();
+    assert!(options.prevent_clipping);
+    assert_eq!(options.factor(&ReplayGain::default()), 1.0);
+    assert_eq!(decode(Some(options)), decode(None));
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::VECTOR;
+use minimp3_fixed::header::{ChannelMode, Emphasis, FrameHeader, Layer, Version};
+use minimp3_fixed::{Decoder, Error};
+
+use std::fs::File;
+
+#[test]
+fn parses_every_field() {
+    let header = FrameHeader::parse(&[0xFF, 0xF2, 0x4B, 0xDD]).unwrap();
+    assert_eq!(header.version, Version::Mpeg2);
+    assert_eq!(header.layer, Layer::Layer3);
+    assert!(header.crc_protected);
+    assert_eq!(header.bitrate, Some(32));
+    assert_eq!(header.sample_rate, 16000);
+    assert!(header.padding);
+    assert!(header.private);
+    assert_eq!(header.channel_mode, ChannelMode::Mono);
+    assert_eq!(header.mode_extension, 1);
+    assert!(header.copyright);
+    assert!(header.original);
+    assert_eq!(header.emphasis, Emphasis::Ms50_15);
+    assert_eq!(header.channels(), 1);
+    assert_eq!(header.samples_per_frame(), 576);
+    assert_eq!(header.frame_length(), Some(72 * 32000 / 16000 + 1));
+}
+
+#[test]
+fn frame_lengths() {
+    // MPEG-1 layer I, 32 kbps, 32 kHz, padded
+    let header = FrameHeader::parse(&[0xFF, 0xFF, 0x1A, 0x00]).unwrap();
+    assert_eq!(header.frame_length(), Some(52));
+    // MPEG-2.5 layer III, free format
+    let header = FrameHeader::parse(&[0xFF, 0xE3, 0x04, 0xC0]).unwrap();
+    assert_eq!(header.version, Version::Mpeg25);
+    assert_eq!(header.sample_rate, 12000);
+    assert_eq!(header.bitrate, None);
+    assert_eq!(header.frame_length(), None);
+}
+
+#[test]
+fn rejects_invalid_headers() {
+    let invalid: &[&[u8]] = &[
+        &[],
+        &[0xFF, 0xFB, 0x90],
+        &[0xFE, 0xFB, 0x90, 0x64],
+        // reserved version
+        &[0xFF, 0xEB, 0x90, 0x64],
+        // reserved layer
+        &[0xFF, 0xF9, 0x90, 0x64],
+        // bad bitrate
+        &[0xFF, 0xFB, 0xF0, 0x64],
+        // reserved sample rate
+        &[0xFF, 0xFB, 0x9C, 0x64],
+        // reserved emphasis
+        &[0xFF, 0xFB, 0x90, 0x66],
+    ];
+    for bytes in invalid {
+        assert_eq!(FrameHeader::parse(bytes), None, "{:x?}", bytes);
+    }
+}
+
+#[test]
+fn walks_frames_like_the_decoder() {
+    let data = std::fs::read(VECTOR).unwrap();
+    let mut decoder = Decoder::new(File::open(VECTOR).unwrap());
+
+    let mut position = 0;
+    loop {
+        let frame = match decoder.next_frame() {
+            Ok(frame) => frame,
+            Err(Error::Eof) => break,
+            Err(e) => panic!("{:?}", e),
+        };
+        let header = FrameHeader::parse(&data[position..]).unwrap();
+        assert_eq!(header.sample_rate as i32, frame.sample_rate);
+        assert_eq!(header.channels(), frame.channels);
+        assert_eq!(header.layer.number(), frame.layer);
+        assert_eq!(header.bitrate, Some(frame.bitrate as u32));
+        assert_eq!(
+            header.samples_per_frame() * frame.channels,
+            frame.data.len()
+        );
+        position += header.frame_length().unwrap();
+    }
+    assert_eq!(position, data.len());
+}
@@ -0,0 +1,276 @@
+mod common;
+
+use common::{frame, id3v2, latin1, syncsafe, unsynchronise, VECTOR};
+use minimp3_fixed::id3::{tag_size, Comment, Content, Picture, Tag};
+use minimp3_fixed::{Decoder, Error, Frame, PushDecoder, SeekDecoder};
+
+use std::io::Cursor;
+
+// Looks like a run of frame headers, to make sure the decoders don't find
+// frames in the tag.
+const FAKE_AUDIO: &[u8] = &[0xFF, 0xFB, 0x90, 0x64, 0xFF, 0x00, 0xFF, 0xE0, 0x12];
+
+fn utf16(text: &str) -> Vec<u8> {
+    let mut body = vec![1, 0xFF, 0xFE];
+    body.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+    body
+}
+
+fn picture_frame() -> Vec<u8> {
+    let mut body = latin1("image/png");
+    body.extend([0, 3]);
+    body.extend(b"cover\0");
+    body.extend(FAKE_AUDIO);
+    frame(3, "APIC", 0, &body)
+}
+
+fn v23_tag() -> Vec<u8> {
+    let mut comment = vec![0];
+    comment.extend(b"engdesc\0text");
+    let mut frames = frame(3, "TIT2", 0, &latin1("Title"));
+    frames.extend(frame(3, "TPE1", 0, &utf16("Ärtist")));
+    frames.extend(frame(3, "COMM", 0, &comment));
+    frames.extend(picture_frame());
+    // Padding
+    frames.extend([0; 20]);
+    id3v2(3, 0x80, &unsynchronise(&frames))
+}
+
+fn frames<R: std::io::Read>(mut decoder: Decoder<R>) -> Vec<Frame> {
+    let mut frames = Vec::new();
+    loop {
+        match decoder.next_frame() {
+            Ok(frame) => frames.push(frame),
+            Err(Error::Eof) => return frames,
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+}
+
+#[test]
+fn parses_v23_tags() {
+    let data = v23_tag();
+    assert_eq!(tag_size(&data), Some(data.len()));
+    let tag = Tag::parse(&data).unwrap();
+
+    assert_eq!(tag.version, 3);
+    assert_eq!(tag.frames.len(), 4);
+    assert_eq!(tag.title(), Some("Title"));
+    assert_eq!(tag.artist(), Some("Ärtist"));
+    assert_eq!(tag.album(), None);
+    assert_eq!(
+        tag.comments().collect::<Vec<_>>(),
+        [&Comment {
+            language: "eng".to_owned(),
+            description: "desc".to_owned(),
+            text: "text".to_owned(),
+        }]
+    );
+    assert_eq!(
+        tag.pictures().collect::<Vec<_>>(),
+        [&Picture {
+            mime_type: "image/png".to_owned(),
+            picture_type: 3,
+            description: "cover".to_owned(),
+            data: FAKE_AUDIO.to_vec(),
+        }]
+    );
+}
+
+#[test]
+fn skips_extended_headers() {
+    let mut body = vec![0, 0, 0, 6, 0, 0, 0, 0, 0, 0];
+    body.extend(frame(3, "TALB", 0, &latin1("Album")));
+    let tag = Tag::parse(&id3v2(3, 0x40, &body)).unwrap();
+    assert_eq!(tag.album(), Some("Album"));
+
+    let mut body = vec![0, 0, 0, 6, 1, 0];
+    body.extend(frame(4, "TALB", 0, &latin1("Album")));
+    let tag = Tag::parse(&id3v2(4, 0x40, &body)).unwrap();
+    assert_eq!(tag.album(), Some("Album"));
+}
+
+#[test]
+fn parses_v24_frames() {
+    let text = [&[3][..], "Rock\0Pop".as_bytes()].concat();
+    let compressed = miniz_oxide::deflate::compress_to_vec_zlib(&latin1("Zipped"), 6);
+    let mut compressed_body = syncsafe(7).to_vec();
+    compressed_body.extend(compressed);
+    let mut url = vec![0];
+    url.extend(b"home\0http://example.com");
+
+    let mut frames = frame(4, "TCON", 0, &text);
+    frames.extend(frame(4, "TIT2", 0x09, &compressed_body));
+    frames.extend(frame(4, "TXXX", 0, &latin1("key\0value")));
+    frames.extend(frame(4, "WXXX", 0, &url));
+    frames.extend(frame(4, "WOAR", 0, b"http://example.org"));
+    frames.extend(frame(4, "TPE1", 0x02, &unsynchronise(&[0, 0xFF])));
+    frames.extend(frame(4, "TPE2", 0x04, &[1, 2, 3]));
+    let tag = Tag::parse(&id3v2(4, 0, &frames)).unwrap();
+
+    assert_eq!(
+        tag.get("TCON").unwrap().content,
+        Content::Text(vec!["Rock".to_owned(), "Pop".to_owned()])
+    );
+    assert_eq!(tag.title(), Some("Zipped"));
+    assert_eq!(
+        tag.get("TXXX").unwrap().content,
+        Content::ExtendedText {
+            description: "key".to_owned(),
+            value: "value".to_owned(),
+        }
+    );
+    assert_eq!(
+        tag.get("WXXX").unwrap().content,
+        Content::ExtendedUrl {
+            description: "home".to_owned(),
+            url: "http://example.com".to_owned(),
+        }
+    );
+    assert_eq!(
+        tag.get("WOAR").unwrap().content,
+        Content::Url("http://example.org".to_owned())
+    );
+    assert_eq!(tag.artist(), Some("ÿ"));
+    // Encrypted
+    assert_eq!(
+        tag.get("TPE2").unwrap().content,
+        Content::Unknown(vec![1, 2, 3])
+    );
+}
+
+#[test]
+fn maps_v22_frames() {
+    let mut picture = vec![0];
+    picture.extend(b"JPG\x00\0");
+    picture.extend(FAKE_AUDIO);
+    let mut frames = frame(2, "TT2", 0, &latin1("Title"));
+    frames.extend(frame(2, "PIC", 0, &picture));
+    frames.extend(frame(2, "XYZ", 0, &[1]));
+    let tag = Tag::parse(&id3v2(2, 0, &frames)).unwrap();
+
+    assert_eq!(tag.title(), Some("Title"));
+    let picture = tag.pictures().next().unwrap();
+    assert_eq!(picture.mime_type, "image/jpeg");
+    assert_eq!(picture.picture_type, 0);
+    assert_eq!(picture.data, FAKE_AUDIO);
+    assert_eq!(tag.frames[2].id, "XYZ");
+}
+
+#[test]
+fn rejects_other_data() {
+    assert_eq!(tag_size(b"ID3"), None);
+    assert_eq!(tag_size(b"ID3\x05\0\0\0\0\0\0"), None);
+    assert_eq!(tag_size(b"ID3\x03\0\0\0\0\x80\0"), None);
+    assert_eq!(
+        Tag::parse(&[0xFF, 0xFB, 0x90, 0x64, 0, 0, 0, 0, 0, 0]),
+        None
+    );
+    assert_eq!(Tag::read_from(&mut Cursor::new(b"ID3")).unwrap(), None);
+    // A tag cut off by the end of the stream.
+    let tag = v23_tag();
+    let mut reader = Cursor::new(&tag[..tag.len() - 1]);
+    assert_eq!(Tag::read_from(&mut reader).unwrap(), None);
+    // The largest size a header can give, without the data.
+    let mut reader = Cursor::new(b"ID3\x04\0\0\x7F\x7F\x7F\x7F");
+    assert_eq!(Tag::read_from(&mut reader).unwrap(), None);
+}
+
+#[test]
+fn reads_appended_tags() {
+    let frames = frame(4, "TIT2", 0, &latin1("Appended"));
+    let mut appended = id3v2(4, 0x10, &frames);
+    let mut footer = appended[..10].to_vec();
+    footer[..3].copy_from_slice(b"3DI");
+    appended.extend(footer);
+    assert_eq!(tag_size(&appended), Some(appended.len()));
+
+    let mut data = std::fs::read(VECTOR).unwrap();
+    data.extend(&appended);
+    let tag = Tag::read_appended(&mut Cursor::new(&data))
+        .unwrap()
+        .unwrap();
+    assert_eq!(tag.title(), Some("Appended"));
+
+    // Followed by an ID3v1 tag
+    data.extend(b"TAG");
+    data.extend([0; 125]);
+    let decoder = SeekDecoder::new(Cursor::new(data)).unwrap();
+    assert_eq!(decoder.tag().unwrap().title(), Some("Appended"));
+}
+
+#[test]
+fn decoders_skip_the_tag() {
+    let audio = std::fs::read(VECTOR).unwrap();
+    let tag = v23_tag();
+    let data = [&tag[..], &audio].concat();
+    let reference = frames(Decoder::new(Cursor::new(audio)));
+
+    let mut decoder = Decoder::new(Cursor::new(data.clone()));
+    assert_eq!(decoder.tag(), None);
+    assert_eq!(decoder.read_tag().unwrap().unwrap().title(), Some("Title"));
+    let tagged = frames(decoder);
+    assert_eq!(tagged.len(), reference.len());
+    assert_eq!(tagged[0].offset, Some(tag.len() as u64));
+    assert_eq!(tagged[0].skipped_bytes, 0);
+    for (frame, expected) in tagged.iter().zip(&reference) {
+        assert_eq!(frame.data, expected.data);
+    }
+
+    let mut decoder = PushDecoder::new();
+    for chunk in data.chunks(7) {
+        decoder.feed(chunk);
+        while decoder.next_frame().is_ok() {}
+    }
+    assert_eq!(decoder.tag().unwrap().artist(), Some("Ärtist"));
+
+    let decoder = SeekDecoder::new(Cursor::new(data)).unwrap();
+    assert_eq!(decoder.tag().unwrap().title(), Some("Title"));
+}
+
+#[test]
+fn decoders_skip_oversized_tags() {
+    let audio = std::fs::read(VECTOR).unwrap();
+    let reference = frames(Decoder::new(Cursor::new(audio.clone())));
+    let tag = id3v2(4, 0, &vec![0; 9 * 1024 * 1024]);
+    let data = [&tag[..], &audio].concat();
+
+    let mut decoder = Decoder::new(Cursor::new(data.clone()));
+    assert_eq!(decoder.read_tag().unwrap(), None);
+    let skipped = frames(decoder);
+    assert_eq!(skipped.len(), reference.len());
+    assert_eq!(skipped[0].offset, Some(tag.len() as u64));
+    assert_eq!(skipped[0].skipped_bytes, 0);
+    assert_eq!(skipped[0].data, reference[0].data);
+
+    // The tag isn't buffered while it arrives.
+    let mut decoder = PushDecoder::new();
+    for chunk in tag.chunks(64 * 1024) {
+        decoder.feed(chunk);
+        assert!(matches!(decoder.next_frame(), Err(Error::InsufficientData)));
+        assert_eq!(decoder.buffered_bytes(), 0);
+    }
+
+    // The largest size a header can give, without the data.
+    let mut data = b"ID3\x04\0\0\x7F\x7F\x7F\x7F".to_vec();
+    data.extend(&audio);
+    let mut decoder = Decoder::new(Cursor::new(data));
+    assert!(matches!(decoder.next_frame(), Err(Error::Eof)));
+}
+
+#[cfg(feature = "async_futures")]
+#[test]
+fn futures_decoder_reads_the_tag() {
+    let audio = std::fs::read(VECTOR).unwrap();
+    let data = [&v23_tag()[..], &audio].concat();
+    let reference = frames(Decoder::new(Cursor::new(audio)));
+
+    futures::executor::block_on(async {
+        let reader = minimp3_fixed::FuturesIo::new(futures::io::Cursor::new(data));
+        let mut decoder = Decoder::new(reader);
+        let tag = decoder.read_tag_future().await.unwrap().unwrap();
+        assert_eq!(tag.title(), Some("Title"));
+        let frame = decoder.next_frame_future().await.unwrap();
+        assert_eq!(frame.data, reference[0].data);
+    });
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use common::VECTOR;
+use minimp3_fixed::{Decoder, Error, SeekDecoder};
+
+use std::fs::File;
+
+fn decoded_samples() -> Vec<i16> {
+    let mut decoder = Decoder::new(File::open(VECTOR).unwrap());
+    let mut samples = Vec::new();
+    loop {
+        match decoder.next_frame() {
+            Ok(frame) => samples.extend(frame.data),
+            Err(Error::Eof) => return samples,
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+}
+
+#[test]
+fn decoder_iterates_until_eof() {
+    let decoder = Decoder::new(File::open(VECTOR).unwrap());
+    let samples: Vec<i16> = decoder.flat_map(|frame| frame.unwrap().data).collect();
+    assert_eq!(samples, decoded_samples());
+}
+
+#[test]
+fn decoder_samples() {
+    let mut decoder = Decoder::new(File::open(VECTOR).unwrap());
+    let samples: Result<Vec<i16>, Error> = decoder.samples().collect();
+    assert_eq!(samples.unwrap(), decoded_samples());
+    assert!(decoder.next().is_none());
+}
+
+#[test]
+fn seek_decoder_samples_match_frames() {
+    let mut frames = SeekDecoder::new(File::open(VECTOR).unwrap()).unwrap();
+    let mut samples = SeekDecoder::new(File::open(VECTOR).unwrap()).unwrap();
+
+    let from_frames: Vec<i16> = frames
+        .by_ref()
+        .flat_map(|frame| frame.unwrap().data)
+        .collect();
+    let from_samples: Vec<i16> = samples.samples().map(Result::unwrap).collect();
+
+    assert!(!from_frames.is_empty());
+    assert_eq!(from_frames, from_samples);
+    assert!(frames.next().is_none());
+}
+
+#[cfg(feature = "async_tokio")]
+#[tokio::test]
+async fn decoder_streams_until_eof() {
+    use futures::StreamExt;
+
+    let decoder = Decoder::new(tokio::fs::File::open(VECTOR).await.unwrap());
+    let frames: Vec<_> = decoder.collect().await;
+    let samples: Vec<i16> = frames
+        .into_iter()
+        .flat_map(|frame| frame.unwrap().data)
+        .collect();
+
+    assert_eq!(samples, decoded_samples());
+}
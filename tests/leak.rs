@@ -0,0 +1,95 @@
+mod common;
+
+use common::VECTOR;
+use minimp3_fixed::{DecodedAudio, SeekDecoder};
+
+use std::fs::File;
+use std::ops::ControlFlow;
+
+const ITERATIONS: usize = 4000;
+
+// Resident set size of this process in bytes, read from procfs.
+#[cfg(target_os = "linux")]
+fn resident_bytes() -> usize {
+    let statm = std::fs::read_to_string("/proc/self/statm").unwrap();
+    let pages: usize = statm.split_whitespace().nth(1).unwrap().parse().unwrap();
+    pages * 4096
+}
+
+fn open() -> SeekDecoder<File> {
+    SeekDecoder::new(File::open(VECTOR).unwrap()).unwrap()
+}
+
+// Every leaked decoder holds on to minimp3's read buffer (128 KiB) plus the
+// seek index, so thousands of leaks would add hundreds of megabytes.
+#[test]
+#[cfg(target_os = "linux")]
+fn dropped_decoders_release_memory() {
+    for _ in 0..16 {
+        drop(open());
+    }
+    let before = resident_bytes();
+
+    for i in 0..ITERATIONS {
+        if i % 2 == 0 {
+            drop(open());
+        } else {
+            let _file: File = open().into_inner();
+        }
+    }
+
+    let grown = resident_bytes().saturating_sub(before);
+    assert!(
+        grown < 32 * 1024 * 1024,
+        "resident memory grew by {} bytes after {} decoders",
+        grown,
+        ITERATIONS
+    );
+}
+
+#[test]
+#[cfg(not(target_os = "linux"))]
+fn dropped_decoders_release_memory() {
+    for _ in 0..ITERATIONS {
+        drop(open());
+    }
+}
+
+// The samples `decode_all` returns are copied out of a buffer minimp3
+// allocated, which is freed also when decoding is cancelled.
+#[test]
+#[cfg(target_os = "linux")]
+fn decode_all_releases_memory() {
+    let data = std::fs::read(VECTOR).unwrap();
+    let decode = |cancel_at: usize| {
+        let mut calls = 0;
+        let mut progress = |_| {
+            calls += 1;
+            if calls == cancel_at {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        };
+        DecodedAudio::<i16>::decode(std::io::Cursor::new(&data), Some(&mut progress))
+    };
+    let full = decode(0).unwrap().samples.len() * 2;
+    for _ in 0..16 {
+        drop(decode(0));
+    }
+    let before = resident_bytes();
+
+    let iterations = (256 * 1024 * 1024 / full.max(1)).clamp(64, ITERATIONS);
+    for i in 0..iterations {
+        let cancel_at = if i % 2 == 0 { 0 } else { 4 };
+        drop(decode(cancel_at));
+    }
+
+    let grown = resident_bytes().saturating_sub(before);
+    assert!(
+        grown < 32 * 1024 * 1024,
+        "resident memory grew by {} bytes after {} decodes",
+        grown,
+        iterations
+    );
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use common::{frame, id3v2, VECTOR};
+use minimp3_fixed::header::FrameHeader;
+use minimp3_fixed::id3::{Lyrics, LyricsCursor, SyncedLine, SyncedLyrics, Tag, TimestampFormat};
+use minimp3_fixed::{FrameInfo, SeekDecoder};
+
+use std::io::Cursor;
+
+fn utf16(text: &str) -> Vec<u8> {
+    let mut out = vec![0xFF, 0xFE];
+    out.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+    out.extend([0, 0]);
+    out
+}
+
+// A SYLT frame in UTF-16, where every string has its own byte order mark.
+fn sylt(format: u8, lines: &[(&str, u32)]) -> Vec<u8> {
+    let mut body = vec![1];
+    body.extend(b"eng");
+    body.extend([format, 1]);
+    body.extend(utf16("Karaoke"));
+    for (text, time) in lines {
+        body.extend(utf16(text));
+        body.extend(time.to_be_bytes());
+    }
+    frame(3, "SYLT", 0, &body)
+}
+
+fn lines(lines: &[(&str, u32)]) -> Vec<SyncedLine> {
+    lines
+        .iter()
+        .map(|&(text, time)| SyncedLine {
+            time,
+            text: text.to_owned(),
+        })
+        .collect()
+}
+
+fn synced(format: TimestampFormat, times: &[(&str, u32)]) -> SyncedLyrics {
+    SyncedLyrics {
+        language: "eng".to_owned(),
+        description: String::new(),
+        timestamp_format: format,
+        content_type: 1,
+        lines: lines(times),
+    }
+}
+
+#[test]
+fn parses_lyrics() {
+    let mut uslt = vec![0];
+    uslt.extend(b"engverse\0Line one\nLine two");
+    let mut frames = frame(3, "USLT", 0, &uslt);
+    frames.extend(sylt(2, &[("Two", 500), ("One", 0), ("Three", 1000)]));
+    let tag = Tag::parse(&id3v2(3, 0, &frames)).unwrap();
+
+    assert_eq!(
+        tag.lyrics().collect::<Vec<_>>(),
+        [&Lyrics {
+            language: "eng".to_owned(),
+            description: "verse".to_owned(),
+            text: "Line one\nLine two".to_owned(),
+        }]
+    );
+    let synced = tag.synced_lyrics().next().unwrap();
+    assert_eq!(synced.language, "eng");
+    assert_eq!(synced.description, "Karaoke");
+    assert_eq!(synced.timestamp_format, TimestampFormat::Milliseconds);
+    assert_eq!(synced.content_type, 1);
+    assert_eq!(
+        synced.lines,
+        lines(&[("One", 0), ("Two", 500), ("Three", 1000)])
+    );
+}
+
+#[test]
+fn cursor_converts_times() {
+    let lyrics = synced(TimestampFormat::Milliseconds, &[("a", 100), ("b", 1000)]);
+    let mut cursor = LyricsCursor::new(lyrics, 1000, 2, 1152);
+    assert_eq!(cursor.line_at(0), None);
+    assert_eq!(cursor.line_at(199), None);
+    assert_eq!(cursor.line_at(200).unwrap().text, "a");
+    assert_eq!(cursor.line_at(5000).unwrap().text, "b");
+    // Seeking back
+    assert_eq!(cursor.line_at(300).unwrap().text, "a");
+    assert_eq!(cursor.line_at(0), None);
+
+    // MPEG-1 layer III, 1152 samples per frame.
+    let header = FrameHeader::parse(&[0xFF, 0xFB, 0x90, 0x64]).unwrap();
+    let mut info = FrameInfo {
+        samples: 2304,
+        sample_rate: 44100,
+        channels: 2,
+        layer: 3,
+        bitrate: 128,
+        offset: None,
+        frame_bytes: 417,
+        skipped_bytes: 0,
+        header: Some(header),
+        sample_position: 0,
+    };
+    let frames = synced(TimestampFormat::MpegFrames, &[("a", 0), ("b", 10)]);
+    let mut cursor = LyricsCursor::for_frame(frames.clone(), &info);
+    assert_eq!(cursor.line_at(9 * 2304).unwrap().text, "a");
+    assert_eq!(cursor.line_at(10 * 2304).unwrap().text, "b");
+
+    // Without a header, MPEG-2 layer III has 576 samples per frame.
+    info.header = None;
+    info.sample_rate = 22050;
+    assert_eq!(info.samples_per_frame(), 576);
+    let mut cursor = LyricsCursor::for_frame(frames, &info);
+    assert_eq!(cursor.line_at(10 * 1152 - 1).unwrap().text, "a");
+    assert_eq!(cursor.line_at(10 * 1152).unwrap().text, "b");
+}
+
+#[test]
+fn follows_seek_decoder() {
+    let frames = [
+        sylt(2, &[("One", 0), ("Two", 500), ("Three", 1000)]),
+        sylt(1, &[("First", 0), ("Fourth", 3)]),
+    ]
+    .concat();
+    let data = [id3v2(3, 0, &frames), std::fs::read(VECTOR).unwrap()].concat();
+    let mut decoder = SeekDecoder::new(Cursor::new(data)).unwrap();
+    let lyrics: Vec<_> = decoder.tag().unwrap().synced_lyrics().cloned().collect();
+    let mut ms = decoder.lyrics_cursor(lyrics[0].clone());
+    let mut frames = decoder.lyrics_cursor(lyrics[1].clone());
+
+    for index in 0..4 {
+        let frame = decoder.decode_frame().unwrap();
+        assert_eq!(ms.line_at(frame.sample_position).unwrap().text, "One");
+        let expected = if index < 3 { "First" } else { "Fourth" };
+        assert_eq!(
+            frames.line_at(frame.sample_position).unwrap().text,
+            expected
+        );
+    }
+
+    let rate = decoder.current_sample_rate() as u64;
+    let channels = decoder._current_channels() as u64;
+    decoder.seek_samples(rate * channels).unwrap();
+    let frame = decoder.decode_frame().unwrap();
+    assert_eq!(ms.line_at(frame.sample_position).unwrap().text, "Three");
+    decoder.seek_samples(0).unwrap();
+    let frame = decoder.decode_frame().unwrap();
+    assert_eq!(ms.line_at(frame.sample_position).unwrap().text, "One");
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use common::VECTOR;
+use minimp3_fixed::{Decoder, Error, Frame, PushDecoder, SeekDecoder};
+
+use std::fs::File;
+use std::io::Cursor;
+
+fn frames(data: Vec<u8>) -> Vec<Frame> {
+    let mut decoder = Decoder::new(Cursor::new(data));
+    let mut frames = Vec::new();
+    loop {
+        match decoder.next_frame() {
+            Ok(frame) => frames.push(frame),
+            Err(Error::Eof) => return frames,
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+}
+
+#[test]
+fn frames_tile_the_stream() {
+    let data = std::fs::read(VECTOR).unwrap();
+    let frames = frames(data.clone());
+
+    let mut offset = 0;
+    let mut sample_position = 0;
+    for frame in &frames {
+        assert_eq!(frame.offset, Some(offset + frame.skipped_bytes as u64));
+        assert_eq!(frame.sample_position, sample_position);
+
+        let header = frame.header.unwrap();
+        assert_eq!(header.frame_length(), Some(frame.frame_bytes));
+        assert_eq!(header.sample_rate as i32, frame.sample_rate);
+        assert_eq!(header.channels(), frame.channels);
+
+        offset = frame.offset.unwrap() + frame.frame_bytes as u64;
+        sample_position += frame.data.len() as u64;
+    }
+    assert_eq!(offset, data.len() as u64);
+}
+
+#[test]
+fn junk_is_reported_as_skipped() {
+    let mut data = vec![0; 1000];
+    data.extend(std::fs::read(VECTOR).unwrap());
+    let frames = frames(data);
+
+    assert_eq!(frames[0].skipped_bytes, 1000);
+    assert_eq!(frames[0].offset, Some(1000));
+    assert_eq!(frames[1].skipped_bytes, 0);
+}
+
+#[test]
+fn push_decoder_reports_the_same_offsets() {
+    let data = std::fs::read(VECTOR).unwrap();
+    let reference = frames(data.clone());
+
+    let mut decoder = PushDecoder::new();
+    let mut pushed = Vec::new();
+    for chunk in data.chunks(100) {
+        decoder.feed(chunk);
+        while let Ok(frame) = decoder.next_frame() {
+            pushed.push(frame);
+        }
+    }
+    decoder.finish();
+    while let Ok(frame) = decoder.next_frame() {
+        pushed.push(frame);
+    }
+
+    assert_eq!(pushed.len(), reference.len());
+    for (frame, expected) in pushed.iter().zip(&reference) {
+        assert_eq!(frame.info(), expected.info());
+    }
+}
+
+#[test]
+fn seek_decoder_tracks_sample_position() {
+    let mut decoder = SeekDecoder::new(File::open(VECTOR).unwrap()).unwrap();
+
+    let mut sample_position = 0;
+    for frame in decoder.by_ref().take(10) {
+        let frame = frame.unwrap();
+        assert_eq!(frame.sample_position, sample_position);
+        sample_position += frame.data.len() as u64;
+    }
+
+    decoder.seek_samples(5000).unwrap();
+    assert_eq!(decoder.decode_frame().unwrap().sample_position, 5000);
+}
+
+#[test]
+fn seek_decoder_finds_frames_in_the_stream() {
+    let data = std::fs::read(VECTOR).unwrap();
+    let reference = frames(data.clone());
+
+    let from_reader = SeekDecoder::new(File::open(VECTOR).unwrap()).unwrap();
+    let from_slice = SeekDecoder::from_slice(&data).unwrap();
+    for decoder in [
+        Box::new(from_reader) as Box<dyn Iterator<Item = _>>,
+        Box::new(from_slice),
+    ] {
+        let frames: Vec<_> = decoder.map(Result::unwrap).collect();
+        assert_eq!(frames.len(), reference.len());
+        for (frame, expected) in frames.iter().zip(&reference) {
+            assert_eq!(frame.offset, expected.offset);
+            assert_eq!(frame.header, expected.header);
+            assert_eq!(frame.frame_bytes, expected.frame_bytes);
+        }
+    }
+
+    // Frames read in parts describe the same frame.
+    let mut decoder = SeekDecoder::new(File::open(VECTOR).unwrap()).unwrap();
+    let mut buf = [0; 100];
+    let mut samples = 0;
+    for expected in &reference[..3] {
+        while samples < expected.sample_position + expected.data.len() as u64 {
+            let info = decoder.next_frame_into(&mut buf).unwrap();
+            assert_eq!(info.offset, expected.offset);
+            assert_eq!(info.header, expected.header);
+            assert_eq!(info.sample_position, samples);
+            samples += info.samples as u64;
+        }
+    }
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use common::{ape_tag, frame, id3v2, syncsafe, unsynchronise};
+use minimp3_fixed::id3::Picture;
+use minimp3_fixed::picture::{find_pictures, read_pictures};
+
+use std::io::{Cursor, Read};
+
+// A JPEG-ish image, full of bytes unsynchronisation changes.
+fn image(len: usize) -> Vec<u8> {
+    let mut image = vec![0xFF, 0xD8];
+    image.extend((0..len - 2).map(|i| if i % 3 == 0 { 0xFF } else { i as u8 }));
+    image
+}
+
+fn apic(picture_type: u8, description: &str, data: &[u8]) -> Vec<u8> {
+    // UTF-16 with a byte order mark
+    let mut body = vec![1];
+    body.extend(b"image/jpeg\0");
+    body.push(picture_type);
+    body.extend([0xFF, 0xFE]);
+    body.extend(description.encode_utf16().flat_map(u16::to_le_bytes));
+    body.extend([0, 0]);
+    body.extend(data);
+    body
+}
+
+fn ape_cover(data: &[u8]) -> Vec<u8> {
+    let mut value = b"back.png\0".to_vec();
+    value.extend(data);
+    ape_tag(&[("Cover Art (Back)", 2, &value)], false)
+}
+
+#[test]
+fn finds_pictures_in_every_tag() {
+    let front = image(300_000);
+    let artist = image(100);
+    let back = image(5000);
+    // Longer than the first read of the picture fields
+    let description = "é".repeat(2000);
+
+    let mut frames = frame(3, "TIT2", 0, b"\0Title");
+    frames.extend(frame(3, "APIC", 0, &apic(3, &description, &front)));
+    frames.extend(frame(3, "APIC", 0, &apic(8, "", &artist)));
+    let mut data = id3v2(3, 0, &frames);
+    data.extend([0x55; 1000]);
+    data.extend(ape_cover(&back));
+    data.extend(b"TAG");
+    data.extend([0; 125]);
+    let mut reader = Cursor::new(data);
+
+    let pictures = find_pictures(&mut reader).unwrap();
+    assert_eq!(pictures.len(), 3);
+    assert_eq!(pictures[0].mime_type, "image/jpeg");
+    assert_eq!(pictures[0].picture_type, 3);
+    assert_eq!(pictures[0].description, description);
+    assert_eq!(pictures[0].size, front.len() as u64);
+    assert_eq!(pictures[1].picture_type, 8);
+    assert_eq!(pictures[2].mime_type, "image/png");
+    assert_eq!(pictures[2].picture_type, 4);
+    assert_eq!(pictures[2].description, "back.png");
+
+    // Stream the front cover in small pieces
+    let mut streamed: Vec<u8> = Vec::new();
+    let mut image = pictures[0].open(&mut reader).unwrap();
+    let mut buf = [0; 1000];
+    loop {
+        match image.read(&mut buf).unwrap() {
+            0 => break,
+            n => streamed.extend(&buf[..n]),
+        }
+    }
+    assert_eq!(streamed, front);
+    assert_eq!(pictures[1].read(&mut reader).unwrap(), artist);
+    assert_eq!(pictures[2].read(&mut reader).unwrap(), back);
+}
+
+#[test]
+fn reads_stored_pictures() {
+    let compressed = image(4000);
+    let unsynchronised = image(3000);
+
+    let zipped = miniz_oxide::deflate::compress_to_vec_zlib(&apic(3, "zip", &compressed), 6);
+    let mut body = syncsafe(zipped.len()).to_vec();
+    body.extend(zipped);
+    let mut frames = frame(4, "APIC", 0x09, &body);
+    let body = unsynchronise(&apic(4, "unsync", &unsynchronised));
+    frames.extend(frame(4, "APIC", 0x02, &body));
+    // An encrypted frame is skipped
+    frames.extend(frame(4, "APIC", 0x04, &[1, 2, 3]));
+
+    let pictures = read_pictures(&mut Cursor::new(id3v2(4, 0, &frames))).unwrap();
+    assert_eq!(
+        pictures,
+        [
+            Picture {
+                mime_type: "image/jpeg".to_owned(),
+                picture_type: 3,
+                description: "zip".to_owned(),
+                data: compressed,
+            },
+            Picture {
+                mime_type: "image/jpeg".to_owned(),
+                picture_type: 4,
+                description: "unsync".to_owned(),
+                data: unsynchronised.clone(),
+            },
+        ]
+    );
+
+    // The whole tag is unsynchronised
+    let frames = frame(3, "APIC", 0, &apic(0, "", &unsynchronised));
+    let tag = id3v2(3, 0x80, &unsynchronise(&frames));
+    let pictures = read_pictures(&mut Cursor::new(tag)).unwrap();
+    assert_eq!(pictures.len(), 1);
+    assert_eq!(pictures[0].data, unsynchronised);
+}
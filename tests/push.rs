@@ -0,0 +1,80 @@
+mod common;
+
+use common::VECTOR;
+use minimp3_fixed::{Decoder, Error, Frame, PushDecoder};
+
+use std::fs::File;
+
+fn reference() -> Vec<Frame> {
+    let mut decoder = Decoder::new(File::open(VECTOR).unwrap());
+    let mut frames = Vec::new();
+    loop {
+        match decoder.next_frame() {
+            Ok(frame) => frames.push(frame),
+            Err(Error::Eof) => return frames,
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+}
+
+fn drain(decoder: &mut PushDecoder, frames: &mut Vec<Frame>) -> Error {
+    loop {
+        match decoder.next_frame() {
+            Ok(frame) => frames.push(frame),
+            Err(e) => return e,
+        }
+    }
+}
+
+#[test]
+fn packets_decode_like_a_reader() {
+    let data = std::fs::read(VECTOR).unwrap();
+    let reference = reference();
+
+    for &packet in &[1, 100, 1500, 64 * 1024] {
+        let mut decoder = PushDecoder::new();
+        let mut frames = Vec::new();
+
+        for chunk in data.chunks(packet) {
+            decoder.feed(chunk);
+            let err = drain(&mut decoder, &mut frames);
+            assert!(matches!(err, Error::InsufficientData), "{:?}", err);
+        }
+        decoder.finish();
+        let err = drain(&mut decoder, &mut frames);
+        assert!(matches!(err, Error::Eof), "{:?}", err);
+
+        assert_eq!(frames.len(), reference.len());
+        for (frame, expected) in frames.iter().zip(&reference) {
+            assert_eq!(frame.info(), expected.info());
+            assert_eq!(frame.data, expected.data);
+        }
+    }
+}
+
+#[test]
+fn frames_come_out_before_the_end() {
+    let data = std::fs::read(VECTOR).unwrap();
+    let mut decoder = PushDecoder::new();
+
+    decoder.feed(&data[..4096]);
+    let mut frames = Vec::new();
+    drain(&mut decoder, &mut frames);
+
+    assert!(!frames.is_empty());
+    assert!(decoder.buffered_bytes() < 4096);
+}
+
+#[test]
+fn holds_incomplete_frames() {
+    let data = std::fs::read(VECTOR).unwrap();
+    let mut decoder = PushDecoder::new();
+
+    decoder.feed(&data[..3]);
+    assert!(matches!(decoder.next_frame(), Err(Error::InsufficientData)));
+    assert_eq!(decoder.buffered_bytes(), 3);
+
+    decoder.finish();
+    assert!(matches!(decoder.next_frame(), Err(Error::Eof)));
+    assert_eq!(decoder.buffered_bytes(), 0);
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use common::vectors;
+use minimp3_fixed::index::{IndexCheck, IndexFrame, SeekIndex, VERSION};
+use minimp3_fixed::{Error, SeekDecoder, SeekDecoderBuilder, SeekMode};
+
+use std::fs::File;
+use std::io::Cursor;
+
+fn read_to_end<R: std::io::Read + std::io::Seek>(decoder: &mut SeekDecoder<R>) -> Vec<i16> {
+    let mut samples = Vec::new();
+    let mut buf = vec![0; 4096];
+    while let Some(slice) = decoder.read_sample_slice(&mut buf).unwrap() {
+        samples.extend_from_slice(slice);
+    }
+    samples
+}
+
+#[test]
+fn round_trips_format() {
+    let index = SeekIndex {
+        size: 123_456,
+        hash: Some(0x0123_4567_89AB_CDEF),
+        start_offset: 417,
+        delay: 1105 * 2,
+        padding: 300 * 2,
+        samples: 44100 * 2,
+        detected_samples: 44100 * 2,
+        frames: (0..40)
+            .map(|i| IndexFrame {
+                sample: i * 2304,
+                offset: 417 + i * 418,
+            })
+            .collect(),
+    };
+    let data = index.to_bytes().unwrap();
+    assert_eq!(&data[..5], b"M3SI\x01");
+    assert_eq!(data[4], VERSION);
+    // The frames take four bytes each.
+    assert!(data.len() < 40 * 4 + 64);
+    assert_eq!(SeekIndex::parse(&data), Some(index.clone()));
+
+    let unhashed = SeekIndex {
+        hash: None,
+        frames: Vec::new(),
+        ..index
+    };
+    assert_eq!(
+        SeekIndex::parse(&unhashed.to_bytes().unwrap()),
+        Some(unhashed)
+    );
+
+    // Frames out of order can't be written.
+    let mut unordered = index.clone();
+    unordered.frames.swap(1, 2);
+    assert!(matches!(unordered.to_bytes(), Err(Error::InvalidIndex)));
+    let mut before_start = index.clone();
+    before_start.frames[0].offset = 0;
+    assert!(matches!(before_start.to_bytes(), Err(Error::InvalidIndex)));
+
+    let mut other_version = data.clone();
+    other_version[4] = VERSION + 1;
+    assert_eq!(SeekIndex::parse(&other_version), None);
+    assert_eq!(SeekIndex::parse(&data[..data.len() - 1]), None);
+    assert_eq!(SeekIndex::parse(&[data.clone(), vec![0]].concat()), None);
+    assert_eq!(SeekIndex::parse(b"ID3\x03"), None);
+}
+
+#[test]
+fn reopens_with_index() {
+    for path in vectors() {
+        let mut decoder = SeekDecoder::new(File::open(&path).unwrap()).unwrap();
+        let expected = read_to_end(&mut decoder);
+        let channels = decoder._current_channels() as u64;
+        let frames = expected.len() as u64 / channels;
+
+        for check in [IndexCheck::Size, IndexCheck::Hash] {
+            // Exporting doesn't move the decoder.
+            decoder.seek_samples(channels * (frames / 2)).unwrap();
+            let index = decoder.seek_index(check).unwrap();
+            assert_eq!(decoder.position_samples(), frames / 2);
+            let sample = (channels * (frames / 2)) as usize;
+            assert_eq!(read_to_end(&mut decoder), &expected[sample..], "{:?}", path);
+
+            assert_eq!(index.size, std::fs::metadata(&path).unwrap().len());
+            assert_eq!(index.hash.is_some(), check == IndexCheck::Hash);
+            let index = SeekIndex::parse(&index.to_bytes().unwrap()).unwrap();
+
+            let file = File::open(&path).unwrap();
+            let mut reopened = SeekDecoder::new_with_index(file, &index).unwrap();
+            assert_eq!(reopened.total_samples(), decoder.total_samples());
+            assert_eq!(read_to_end(&mut reopened), expected, "{:?}", path);
+            for frame in [1, frames / 3, frames.saturating_sub(1)] {
+                let sample = frame * channels;
+                reopened.seek_samples(sample).unwrap();
+                let sample = (sample as usize).min(expected.len());
+                assert_eq!(read_to_end(&mut reopened), &expected[sample..]);
+            }
+            assert_eq!(reopened.seek_index(check).unwrap(), index);
+        }
+    }
+}
+
+#[test]
+fn rejects_stale_index() {
+    let path = &vectors()[0];
+    let data = std::fs::read(path).unwrap();
+    let mut decoder = SeekDecoder::new(Cursor::new(data.clone())).unwrap();
+    let index = decoder.seek_index(IndexCheck::Hash).unwrap();
+
+    let mut changed = data.clone();
+    *changed.last_mut().unwrap() ^= 0xFF;
+    let reopened = SeekDecoder::new_with_index(Cursor::new(changed.clone()), &index);
+    assert!(matches!(reopened, Err(Error::StaleIndex)));
+    // Only the size is checked without the hash.
+    let unhashed = SeekIndex {
+        hash: None,
+        ..index.clone()
+    };
+    assert!(SeekDecoder::new_with_index(Cursor::new(changed), &unhashed).is_ok());
+
+    let other_padding = SeekIndex {
+        padding: index.padding + 2,
+        ..index.clone()
+    };
+    let reopened = SeekDecoder::new_with_index(Cursor::new(data.clone()), &other_padding);
+    assert!(matches!(reopened, Err(Error::StaleIndex)));
+
+    let longer = [data.as_slice(), &[0]].concat();
+    let reopened = SeekDecoder::new_with_index(Cursor::new(longer), &index);
+    assert!(matches!(reopened, Err(Error::StaleIndex)));
+
+    // Byte seeking doesn't use an index.
+    let mut decoder = SeekDecoderBuilder::new()
+        .seek_mode(SeekMode::Byte)
+        .build(Cursor::new(data))
+        .unwrap();
+    assert!(matches!(
+        decoder.seek_index(IndexCheck::Size),
+        Err(Error::MiniParam)
+    ));
+}
@@ -1,28 +1,25 @@
-use minimp3_fixed::{Error, Frame, SeekDecoder};
+mod common;
+
+use common::{id3v2, vectors, VECTOR};
+use minimp3_fixed::{Error, Frame, SeekDecoder, SeekDecoderBuilder, SeekMode};
 
 use std::fs::File;
 use std::io::{Read, Seek};
+use std::sync::Arc;
+use std::time::Duration;
 
 fn count_frames(decoder: &mut SeekDecoder<impl Read + Seek>) -> usize {
-    let mut samples = 0;
-    loop {
-        match decoder.decode_frame() {
-            Ok(Frame { data, channels, .. }) => {
-                samples += data.len() / channels;
-            }
-            Err(Error::Eof) => break,
-            Err(Error::InsufficientData) => break,
-            Err(e) => panic!("{:?}", e),
-        }
-    }
-    samples
+    decoder
+        .map(|frame| {
+            let Frame { data, channels, .. } = frame.unwrap();
+            data.len() / channels
+        })
+        .sum()
 }
 
-fn main() {
-    let mut decoder = SeekDecoder::new(
-        File::open("minimp3-sys/minimp3/vectors/M2L3_bitrate_24_all.bit").unwrap(),
-    )
-    .unwrap();
+#[test]
+fn seek_skips_samples() {
+    let mut decoder = SeekDecoder::new(File::open(VECTOR).unwrap()).unwrap();
 
     let before_seek = count_frames(&mut decoder);
 
@@ -31,3 +28,168 @@ fn main() {
 
     assert_eq!(after_seek, before_seek - 100);
 }
+
+fn read_to_end(decoder: &mut SeekDecoder<impl Read + Seek>) -> Vec<i16> {
+    let mut samples = Vec::new();
+    let mut buf = [0; 1000];
+    loop {
+        match decoder.read_samples(&mut buf).unwrap() {
+            0 => return samples,
+            n => samples.extend_from_slice(&buf[..n]),
+        }
+    }
+}
+
+#[test]
+fn time_positions_round_trip() {
+    for path in vectors() {
+        let mut decoder = SeekDecoder::new(File::open(&path).unwrap()).unwrap();
+        let expected = read_to_end(&mut decoder);
+        let channels = decoder._current_channels() as u64;
+        let rate = decoder.current_sample_rate() as u64;
+        let frames = expected.len() as u64 / channels;
+        assert_eq!(decoder.position_samples(), frames, "{:?}", path);
+        let seconds = decoder.duration().as_secs_f64();
+        let total = decoder.total_samples() / channels;
+        assert_eq!((seconds * rate as f64).round() as u64, total, "{:?}", path);
+        if frames == 0 {
+            continue;
+        }
+
+        for frame in [0, 1, frames / 3, frames / 2, frames - 1] {
+            decoder.seek_samples(frame * channels).unwrap();
+            let time = decoder.position();
+            decoder.seek_to(Duration::ZERO).unwrap();
+            decoder.seek_to(time).unwrap();
+            assert_eq!(decoder.position_samples(), frame, "{:?}", path);
+            assert_eq!(decoder.position(), time);
+
+            let sample = (frame * channels) as usize;
+            assert_eq!(read_to_end(&mut decoder), &expected[sample..], "{:?}", path);
+            assert_eq!(decoder.position_samples(), frames);
+        }
+    }
+}
+
+#[test]
+fn seeks_to_times() {
+    let mut decoder = SeekDecoder::new(File::open(VECTOR).unwrap()).unwrap();
+    let channels = decoder._current_channels() as u64;
+    let rate = decoder.current_sample_rate() as u64;
+
+    decoder.seek_to(Duration::from_millis(500)).unwrap();
+    assert_eq!(decoder.position_samples(), rate / 2);
+    assert_eq!(decoder.position(), Duration::from_millis(500));
+    let frame = decoder.decode_frame().unwrap();
+    assert_eq!(frame.sample_position, rate / 2 * channels);
+
+    // Rounds down to a sample.
+    let between = Duration::from_nanos(1_000_000_000 / rate + 1);
+    decoder.seek_to(between).unwrap();
+    assert_eq!(decoder.position_samples(), 1);
+
+    decoder.seek_to(decoder.duration() * 2).unwrap();
+    assert!(matches!(decoder.decode_frame(), Err(Error::Eof)));
+}
+
+#[test]
+fn compares_seek_modes() {
+    let modes = [SeekMode::Sample, SeekMode::Byte];
+    for path in vectors() {
+        let mut reference = SeekDecoder::new(File::open(&path).unwrap()).unwrap();
+        let expected = read_to_end(&mut reference);
+        let channels = reference._current_channels() as u64;
+        let frames = expected.len() as u64 / channels;
+
+        for (mode, scan) in modes.iter().flat_map(|&mode| [(mode, true), (mode, false)]) {
+            let builder = SeekDecoderBuilder::new().seek_mode(mode).scan(scan);
+            let mut decoder = builder.build(File::open(&path).unwrap()).unwrap();
+            assert_eq!(decoder.seek_mode(), mode);
+            if scan {
+                assert_eq!(decoder.total_samples(), reference.total_samples());
+            } else {
+                // Only known from a VBR header.
+                let total = decoder.total_samples();
+                assert!(
+                    total == 0 || total == reference.total_samples(),
+                    "{:?}",
+                    path
+                );
+            }
+            // Decoding from the start doesn't depend on the mode.
+            assert_eq!(read_to_end(&mut decoder), expected, "{:?} {:?}", path, mode);
+            if frames < 8 {
+                continue;
+            }
+
+            let frame = frames / 2;
+            decoder.seek_samples(frame * channels).unwrap();
+            assert_eq!(decoder.position_samples(), frame);
+            let rest = read_to_end(&mut decoder);
+            match mode {
+                SeekMode::Sample => {
+                    let sample = (frame * channels) as usize;
+                    assert_eq!(rest, &expected[sample..], "{:?}", path);
+                }
+                SeekMode::Byte => {
+                    // Lands within a few frames of the sample on these constant
+                    // bitrate vectors.
+                    let first = SeekDecoder::new(File::open(&path).unwrap())
+                        .unwrap()
+                        .decode_frame()
+                        .unwrap();
+                    let tolerance = 3 * first.data.len() as i64;
+                    let expected_len = expected.len() as i64 - (frame * channels) as i64;
+                    let difference = rest.len() as i64 - expected_len;
+                    assert!(difference.abs() <= tolerance, "{:?} {}", path, difference);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn decodes_from_buffers() {
+    for path in vectors() {
+        let data = std::fs::read(&path).unwrap();
+        let mut reference = SeekDecoder::new(File::open(&path).unwrap()).unwrap();
+        let expected = read_to_end(&mut reference);
+        let channels = reference._current_channels() as u64;
+        let middle = expected.len() as u64 / channels / 2 * channels;
+
+        let mut borrowed = SeekDecoder::from_slice(&data).unwrap();
+        assert_eq!(borrowed.total_samples(), reference.total_samples());
+        assert_eq!(read_to_end(&mut borrowed), expected, "{:?}", path);
+        borrowed.seek_samples(middle).unwrap();
+        assert_eq!(read_to_end(&mut borrowed), &expected[middle as usize..]);
+
+        let shared: Arc<[u8]> = data.clone().into();
+        let mut owned = SeekDecoder::from_bytes(shared.clone()).unwrap();
+        owned.seek_samples(middle).unwrap();
+        assert_eq!(read_to_end(&mut owned), &expected[middle as usize..]);
+        assert!(Arc::ptr_eq(owned.into_inner().get_ref(), &shared));
+
+        let mut float = SeekDecoderBuilder::new()
+            .build_bytes_float(data.clone())
+            .unwrap();
+        let mut samples = vec![0.0; expected.len()];
+        assert_eq!(float.read_samples(&mut samples).unwrap(), expected.len());
+    }
+}
+
+#[test]
+fn buffers_keep_tags() {
+    let data = std::fs::read(VECTOR).unwrap();
+    // An empty ID3v2.3 tag in front, an ID3v1 tag behind.
+    let mut tagged = id3v2(3, 0, &[]);
+    tagged.extend(&data);
+    let mut id3v1 = b"TAG".to_vec();
+    id3v1.resize(128, 0);
+    tagged.extend(&id3v1);
+
+    let mut plain = SeekDecoder::from_slice(&data).unwrap();
+    let mut decoder = SeekDecoder::from_bytes(tagged).unwrap();
+    assert!(decoder.trailer().id3v1.is_some());
+    assert_eq!(decoder.total_samples(), plain.total_samples());
+    assert_eq!(read_to_end(&mut decoder), read_to_end(&mut plain));
+}
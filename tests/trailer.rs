@@ -0,0 +1,172 @@
+mod common;
+
+use common::{ape_tag, VECTOR};
+use minimp3_fixed::ape::{ApeTag, ApeValue};
+use minimp3_fixed::id3::V1Tag;
+use minimp3_fixed::trailer::Trailer;
+use minimp3_fixed::{Decoder, Error, Frame, SeekDecoder};
+
+use std::io::Cursor;
+
+fn field(text: &str, len: usize) -> Vec<u8> {
+    let mut field = text.as_bytes().to_vec();
+    field.resize(len, 0);
+    field
+}
+
+fn id3v1(track: u8, genre: u8) -> Vec<u8> {
+    let mut tag = b"TAG".to_vec();
+    tag.extend(field("Short title", 30));
+    tag.extend(field("Artist", 30));
+    tag.extend(field("Album", 30));
+    tag.extend(b"1999");
+    tag.extend(field("Comment", 28));
+    tag.extend([0, track, genre]);
+    tag
+}
+
+fn enhanced() -> Vec<u8> {
+    let mut tag = b"TAG+".to_vec();
+    tag.extend(field("A title longer than the thirty bytes of ID3v1", 60));
+    tag.extend(field("Artist", 60));
+    tag.extend(field("Album", 60));
+    tag.push(2);
+    tag.extend(field("Chiptune", 30));
+    tag.extend(b"000:05001:30");
+    tag
+}
+
+fn ape_with_cover(header: bool) -> Vec<u8> {
+    let items: [(&str, u32, &[u8]); 2] = [
+        ("Title", 0, b"Ape title"),
+        ("Cover Art (Front)", 2, &[0xFF, 0xFB, 0x90]),
+    ];
+    ape_tag(&items, header)
+}
+
+fn frames(mut decoder: Decoder<Cursor<Vec<u8>>>) -> Vec<Frame> {
+    let mut frames = Vec::new();
+    loop {
+        match decoder.next_frame() {
+            Ok(frame) => frames.push(frame),
+            Err(Error::Eof) => return frames,
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+}
+
+#[test]
+fn parses_id3v1_tags() {
+    let tag = V1Tag::parse(&id3v1(7, 17)).unwrap();
+    assert_eq!(tag.title(), "Short title");
+    assert_eq!(tag.artist(), "Artist");
+    assert_eq!(tag.year, "1999");
+    assert_eq!(tag.comment, "Comment");
+    assert_eq!(tag.track, Some(7));
+    assert_eq!(tag.genre_name(), Some("Rock"));
+
+    // ID3v1.0, the comment takes all 30 bytes
+    let tag = V1Tag::parse(&id3v1(0, 255)).unwrap();
+    assert_eq!(tag.track, None);
+    assert_eq!(tag.genre_name(), None);
+
+    let data = [enhanced(), id3v1(1, 191)].concat();
+    let trailer = Trailer::parse(&data, data.len() as u64).unwrap();
+    assert_eq!(trailer.start, 0);
+    let tag = trailer.id3v1.unwrap();
+    assert_eq!(tag.title, "Short title");
+    assert_eq!(tag.title(), "A title longer than the thirty bytes of ID3v1");
+    assert_eq!(tag.genre_name(), Some("Psybient"));
+    let enhanced = tag.enhanced.unwrap();
+    assert_eq!(enhanced.speed, 2);
+    assert_eq!(enhanced.genre, "Chiptune");
+    assert_eq!(enhanced.start_time, "000:05");
+    assert_eq!(enhanced.end_time, "001:30");
+}
+
+#[test]
+fn parses_ape_tags() {
+    for header in [false, true] {
+        let data = ape_with_cover(header);
+        let tag = ApeTag::parse(&data).unwrap();
+        assert_eq!(tag.version, 2000);
+        assert_eq!(tag.title(), Some("Ape title"));
+        assert_eq!(tag.text("TITLE"), Some("Ape title"));
+        assert_eq!(
+            tag.get("cover art (front)").unwrap().value,
+            ApeValue::Binary(vec![0xFF, 0xFB, 0x90])
+        );
+
+        let data = [vec![0; 1000], data].concat();
+        let trailer = Trailer::parse(&data, data.len() as u64).unwrap();
+        assert_eq!(trailer.start, 1000);
+        assert_eq!(trailer.ape, Some(tag));
+    }
+}
+
+#[test]
+fn asks_for_more_of_the_tail() {
+    let data = [vec![1; 100], ape_with_cover(true), id3v1(1, 1)].concat();
+    let len = data.len() as u64;
+    assert_eq!(
+        Trailer::parse(&data[90..], len),
+        Ok(Trailer::parse(&data, len).unwrap())
+    );
+    assert_eq!(Trailer::parse(&data[110..], len), Err(100));
+    assert_eq!(
+        Trailer::parse(&data[data.len() - 20..], len),
+        Err(len - 128)
+    );
+}
+
+#[test]
+fn decoders_stop_before_the_trailer() {
+    let audio = std::fs::read(VECTOR).unwrap();
+    let data = [audio.clone(), ape_with_cover(true), enhanced(), id3v1(3, 8)].concat();
+
+    let reference = frames(Decoder::new(Cursor::new(audio.clone())));
+    let mut decoder = Decoder::new(Cursor::new(data.clone()));
+    let trailer = decoder.read_trailer().unwrap();
+    assert_eq!(trailer.start, audio.len() as u64);
+    assert_eq!(trailer.ape.as_ref().unwrap().title(), Some("Ape title"));
+    assert_eq!(trailer.id3v1.as_ref().unwrap().genre_name(), Some("Jazz"));
+    let trimmed = frames(decoder);
+    assert_eq!(trimmed.len(), reference.len());
+    for (frame, expected) in trimmed.iter().zip(&reference) {
+        assert_eq!(frame.data, expected.data);
+    }
+
+    let reference = SeekDecoder::new(Cursor::new(audio.clone())).unwrap();
+    let decoder = SeekDecoder::new(Cursor::new(data)).unwrap();
+    assert_eq!(decoder.trailer().start, audio.len() as u64);
+    assert_eq!(decoder.total_samples(), reference.total_samples());
+}
+
+#[cfg(feature = "async_futures")]
+#[test]
+fn futures_decoder_reads_the_trailer() {
+    let audio = std::fs::read(VECTOR).unwrap();
+    let data = [audio.clone(), ape_with_cover(true), id3v1(3, 8)].concat();
+    let reference = frames(Decoder::new(Cursor::new(audio.clone())));
+
+    futures::executor::block_on(async {
+        let reader = minimp3_fixed::FuturesIo::new(futures::io::Cursor::new(data));
+        let mut decoder = Decoder::new(reader);
+        let trailer = decoder.read_trailer_future().await.unwrap();
+        assert_eq!(trailer.start, audio.len() as u64);
+        assert_eq!(trailer.id3v1.as_ref().unwrap().genre_name(), Some("Jazz"));
+
+        let mut count = 0;
+        loop {
+            match decoder.next_frame_future().await {
+                Ok(frame) => {
+                    assert_eq!(frame.data, reference[count].data);
+                    count += 1;
+                }
+                Err(Error::Eof) => break,
+                Err(e) => panic!("{:?}", e),
+            }
+        }
+        assert_eq!(count, reference.len());
+    });
+}
@@ -0,0 +1,263 @@
+mod common;
+
+use common::VECTOR;
+use minimp3_fixed::header::{FrameHeader, Version};
+use minimp3_fixed::vbr::{SeekTable, VbrHeader, VbrKind};
+use minimp3_fixed::{Decoder, Error, SeekDecoder};
+
+use std::fs::File;
+use std::io::Cursor;
+use std::time::Duration;
+
+// A silent frame with the same header as `first_frame`, holding a Xing header
+// and a LAME tag.
+fn xing_frame(first_frame: &[u8], frames: u32, delay: u16, padding: u16) -> Vec<u8> {
+    let mut frame = first_frame[..4].to_vec();
+    // Bitrate index 8, so the tags fit.
+    frame[2] = frame[2] & 0x0F | 0x80;
+    let header = FrameHeader::parse(&frame).unwrap();
+    frame.resize(header.frame_length().unwrap(), 0);
+
+    let side_info = match (header.version, header.channels()) {
+        (Version::Mpeg1, 1) => 17,
+        (Version::Mpeg1, _) => 32,
+        (_, 1) => 9,
+        (_, _) => 17,
+    };
+    let crc = if header.crc_protected { 2 } else { 0 };
+    let mut tag = b"Xing".to_vec();
+    tag.extend(&0x1u32.to_be_bytes());
+    tag.extend(&frames.to_be_bytes());
+    tag.extend(lame_tag(delay, padding));
+    let start = 4 + crc + side_info;
+    frame[start..start + tag.len()].copy_from_slice(&tag);
+    frame
+}
+
+// Like `xing_frame`, without the LAME tag.
+fn xing_frame_without_lame(first_frame: &[u8], frames: u32) -> Vec<u8> {
+    let mut frame = xing_frame(first_frame, frames, 0, 0);
+    let start = frame.windows(4).position(|bytes| bytes == b"LAME").unwrap();
+    frame[start..start + lame_tag(0, 0).len()].fill(0);
+    frame
+}
+
+fn lame_tag(delay: u16, padding: u16) -> Vec<u8> {
+    let mut tag = b"LAME3.100".to_vec();
+    // revision 0, VBR method 4, lowpass 19.5 kHz
+    tag.extend(&[0x04, 195]);
+    // peak 0.5
+    tag.extend(&(1u32 << 22).to_be_bytes());
+    // track gain -6.5 dB, album gain +1.2 dB
+    tag.extend(&[0b0010_0010, 65, 0b0100_0000, 12]);
+    tag.extend(&[0, 0]);
+    tag.extend(&[
+        (delay >> 4) as u8,
+        (delay << 4) as u8 | (padding >> 8) as u8,
+        padding as u8,
+    ]);
+    tag.extend(&[0, 0, 0, 0]);
+    tag.extend(&123_456u32.to_be_bytes());
+    tag.extend(&0xBEEFu16.to_be_bytes());
+    tag.extend(&[0, 0]);
+    tag
+}
+
+#[test]
+fn parses_xing() {
+    // MPEG-1 layer III, 128 kbps, 44.1 kHz, joint stereo
+    let mut frame = vec![0xFF, 0xFB, 0x90, 0x64];
+    frame.resize(417, 0);
+    let mut tag = b"Xing".to_vec();
+    tag.extend(&0xFu32.to_be_bytes());
+    tag.extend(&1000u32.to_be_bytes());
+    tag.extend(&417_000u32.to_be_bytes());
+    tag.extend(0..100);
+    tag.extend(&50u32.to_be_bytes());
+    tag.extend(lame_tag(576, 1000));
+    frame[36..36 + tag.len()].copy_from_slice(&tag);
+
+    let vbr = VbrHeader::parse(&frame).unwrap();
+    assert_eq!(vbr.kind, VbrKind::Xing);
+    assert_eq!(vbr.frames, Some(1000));
+    assert_eq!(vbr.bytes, Some(417_000));
+    assert_eq!(vbr.quality, Some(50));
+    match &vbr.seek_table {
+        Some(SeekTable::Xing(toc)) => assert_eq!(toc[99], 99),
+        table => panic!("{:?}", table),
+    }
+
+    let header = FrameHeader::parse(&frame).unwrap();
+    assert_eq!(vbr.total_samples(&header), Some(1000 * 1152 * 2));
+
+    let lame = vbr.lame.unwrap();
+    assert_eq!(lame.encoder, "LAME3.100");
+    assert_eq!(lame.revision, 0);
+    assert_eq!(lame.vbr_method, 4);
+    assert_eq!(lame.lowpass, Some(19500));
+    assert_eq!(lame.peak, Some(0.5));
+    assert_eq!(lame.track_gain, Some(-6.5));
+    assert_eq!(lame.album_gain, Some(1.2));
+    assert_eq!(lame.encoder_delay, 576);
+    assert_eq!(lame.padding, 1000);
+    assert_eq!(lame.music_length, 123_456);
+    assert_eq!(lame.music_crc, 0xBEEF);
+}
+
+#[test]
+fn parses_vbri() {
+    let mut frame = vec![0xFF, 0xFB, 0x90, 0x64];
+    frame.resize(417, 0);
+    let mut tag = b"VBRI".to_vec();
+    for field in &[1u16, 576, 75] {
+        tag.extend(&field.to_be_bytes());
+    }
+    tag.extend(&417_000u32.to_be_bytes());
+    tag.extend(&1000u32.to_be_bytes());
+    // 2 entries of 2 bytes, scaled by 4, 500 frames each
+    for field in &[2u16, 4, 2, 500, 100, 200] {
+        tag.extend(&field.to_be_bytes());
+    }
+    frame[36..36 + tag.len()].copy_from_slice(&tag);
+
+    let vbr = VbrHeader::parse(&frame).unwrap();
+    assert_eq!(vbr.kind, VbrKind::Vbri);
+    assert_eq!(vbr.frames, Some(1000));
+    assert_eq!(vbr.bytes, Some(417_000));
+    assert_eq!(vbr.quality, Some(75));
+    assert_eq!(
+        vbr.seek_table,
+        Some(SeekTable::Vbri {
+            entries: vec![400, 800],
+            frames_per_entry: 500,
+        })
+    );
+}
+
+#[test]
+fn plain_frames_have_no_vbr_header() {
+    let data = std::fs::read(VECTOR).unwrap();
+    assert_eq!(VbrHeader::parse(&data), None);
+}
+
+#[test]
+fn decoder_reads_duration_from_first_frame() {
+    let data = std::fs::read(VECTOR).unwrap();
+    let header = FrameHeader::parse(&data).unwrap();
+    let mut stream = xing_frame(&data, 1000, 0, 0);
+    stream.extend(&data);
+
+    let mut decoder = Decoder::new(Cursor::new(stream));
+    assert_eq!(decoder.total_samples(), None);
+    decoder.next_frame().unwrap();
+
+    let samples = 1000 * (header.samples_per_frame() * header.channels()) as u64;
+    assert_eq!(decoder.vbr_header().unwrap().kind, VbrKind::Xing);
+    assert_eq!(decoder.total_samples(), Some(samples));
+    let seconds = 1000.0 * header.samples_per_frame() as f64 / header.sample_rate as f64;
+    assert_eq!(decoder.duration(), Some(Duration::from_secs_f64(seconds)));
+}
+
+#[test]
+fn seek_decoder_knows_total_samples() {
+    let mut decoder = SeekDecoder::new(File::open(VECTOR).unwrap()).unwrap();
+    let total = decoder.total_samples();
+
+    let mut decoded = 0;
+    loop {
+        match decoder.decode_frame() {
+            Ok(frame) => decoded += frame.data.len() as u64,
+            Err(Error::Eof) => break,
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+    assert_eq!(total, decoded);
+    assert!(decoder.duration() > Duration::ZERO);
+}
+
+fn decode(decoder: &mut Decoder<Cursor<Vec<u8>>>) -> Vec<i16> {
+    let mut samples = Vec::new();
+    let mut position = 0;
+    loop {
+        match decoder.next_frame() {
+            Ok(frame) => {
+                assert_eq!(frame.sample_position, position);
+                position += frame.data.len() as u64;
+                samples.extend(frame.data);
+            }
+            Err(Error::Eof) => return samples,
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+}
+
+#[test]
+fn gapless_trims_delay_and_padding() {
+    let data = std::fs::read(VECTOR).unwrap();
+    let header = FrameHeader::parse(&data).unwrap();
+    let frame_samples = header.samples_per_frame();
+    let channels = header.channels();
+    let frames = Decoder::new(Cursor::new(data.clone())).count();
+
+    let (delay, padding) = (100, 700);
+    let mut stream = xing_frame(&data, frames as u32, delay, padding);
+    stream.extend(&data);
+
+    let untrimmed = decode(&mut Decoder::new(Cursor::new(stream.clone())));
+    let mut decoder = Decoder::new(Cursor::new(stream));
+    decoder.set_gapless(true);
+    let trimmed = decode(&mut decoder);
+
+    let start = (frame_samples + delay as usize + 529) * channels;
+    let len = (frames * frame_samples - delay as usize - padding as usize) * channels;
+    assert_eq!(trimmed.len(), len);
+    assert_eq!(trimmed, &untrimmed[start..start + len]);
+    assert_eq!(decoder.total_samples(), Some(len as u64));
+}
+
+#[test]
+fn gapless_without_lame_tag_drops_only_the_vbr_frame() {
+    let data = std::fs::read(VECTOR).unwrap();
+    let header = FrameHeader::parse(&data).unwrap();
+    let frame_samples = header.samples_per_frame();
+    let channels = header.channels();
+    let frames = Decoder::new(Cursor::new(data.clone())).count();
+
+    let mut stream = xing_frame_without_lame(&data, frames as u32);
+    assert!(VbrHeader::parse(&stream).unwrap().lame.is_none());
+    stream.extend(&data);
+
+    let untrimmed = decode(&mut Decoder::new(Cursor::new(stream.clone())));
+    let mut decoder = Decoder::new(Cursor::new(stream));
+    decoder.set_gapless(true);
+    let trimmed = decode(&mut decoder);
+
+    let start = frame_samples * channels;
+    let len = frames * frame_samples * channels;
+    assert_eq!(trimmed.len(), len);
+    assert_eq!(trimmed, &untrimmed[start..start + len]);
+    assert_eq!(decoder.total_samples(), Some(len as u64));
+}
+
+#[test]
+fn decoders_trim_lame_streams_alike() {
+    let data = std::fs::read(VECTOR).unwrap();
+    let frames = Decoder::new(Cursor::new(data.clone())).count();
+
+    // Padding shorter than the decoder delay isn't trimmed at all.
+    for (delay, padding) in [(576, 1200), (100, 700), (100, 300)] {
+        let mut stream = xing_frame(&data, frames as u32, delay, padding);
+        stream.extend(&data);
+
+        let mut decoder = Decoder::new(Cursor::new(stream.clone()));
+        decoder.set_gapless(true);
+        let trimmed = decode(&mut decoder);
+
+        let mut seek_decoder = SeekDecoder::new(Cursor::new(stream)).unwrap();
+        assert_eq!(seek_decoder.total_samples(), trimmed.len() as u64);
+        assert_eq!(decoder.total_samples(), Some(trimmed.len() as u64));
+        let mut samples = vec![0; trimmed.len() + 4096];
+        let len = seek_decoder.read_samples(&mut samples).unwrap();
+        assert_eq!(len, trimmed.len(), "delay {} padding {}", delay, padding);
+    }
+}